@@ -0,0 +1,799 @@
+//!
+//! Arbitrary-precision unsigned integers.
+//!
+
+use byte_stream::{ByteStream, IntoByteStream};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Rem, Shl, Shr, Sub};
+
+#[derive(Debug)]
+pub struct ParseBigUintError {
+    string: String,
+    valid_up_to: usize,
+}
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid digit in {:?} from index {}",
+            self.string, self.valid_up_to
+        )
+    }
+}
+
+/// Miller-Rabin rounds for generated primes; each round lets a composite
+/// through with probability at most 1/4.
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+/// The primes below 256, for trial division.
+const SMALL_PRIMES: [u32; 54] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251,
+];
+
+/// An unsigned integer of any size, stored as little-endian 32-bit limbs with
+/// no trailing zero limbs.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn one() -> BigUint {
+        BigUint::from(1u64)
+    }
+
+    fn from_limbs(limbs: Vec<u32>) -> BigUint {
+        let mut n = BigUint { limbs };
+        n.normalize();
+        n
+    }
+
+    fn normalize(&mut self) {
+        while let Some(&0) = self.limbs.last() {
+            self.limbs.pop();
+        }
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> BigUint {
+        let mut limbs = Vec::with_capacity(bytes.len() / 4 + 1);
+        for chunk in bytes.rchunks(4) {
+            let mut limb = 0u32;
+            for b in chunk {
+                limb = (limb << 8) | u32::from(*b);
+            }
+            limbs.push(limb);
+        }
+        BigUint::from_limbs(limbs)
+    }
+
+    /// The minimal big-endian encoding; zero encodes as no bytes at all.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.limbs.len() * 4);
+        for limb in self.limbs.iter().rev() {
+            bytes.extend_from_slice(&limb.to_be_bytes());
+        }
+        let leading_zeros = bytes.iter().take_while(|b| **b == 0).count();
+        bytes.split_off(leading_zeros)
+    }
+
+    pub fn from_byte_stream(bs: &ByteStream) -> BigUint {
+        let bytes: Vec<u8> = bs.iter().cloned().collect();
+        BigUint::from_bytes_be(&bytes)
+    }
+
+    pub fn to_byte_stream(&self) -> ByteStream {
+        ByteStream::from(self.to_bytes_be())
+    }
+
+    /// The big-endian encoding left-padded with zeros to exactly `len` bytes.
+    pub fn to_byte_stream_padded(&self, len: usize) -> ByteStream {
+        let bytes = self.to_bytes_be();
+        assert!(bytes.len() <= len, "{} does not fit in {} bytes", self, len);
+        let mut padded = vec![0; len - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        ByteStream::from(padded)
+    }
+
+    pub fn from_hex(s: &str) -> Result<BigUint, ParseBigUintError> {
+        BigUint::from_radix(s, 16)
+    }
+
+    pub fn to_hex(&self) -> String {
+        if self.is_zero() {
+            return String::from("0");
+        }
+        let mut s = format!("{:x}", self.limbs[self.limbs.len() - 1]);
+        for limb in self.limbs.iter().rev().skip(1) {
+            s.push_str(&format!("{:08x}", limb));
+        }
+        s
+    }
+
+    pub fn from_decimal(s: &str) -> Result<BigUint, ParseBigUintError> {
+        BigUint::from_radix(s, 10)
+    }
+
+    pub fn to_decimal(&self) -> String {
+        if self.is_zero() {
+            return String::from("0");
+        }
+        const CHUNK: u32 = 1_000_000_000;
+        let mut chunks = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.divmod_small(CHUNK);
+            chunks.push(r);
+            n = q;
+        }
+        let mut s = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            s.push_str(&format!("{:09}", chunk));
+        }
+        s
+    }
+
+    fn from_radix(s: &str, radix: u32) -> Result<BigUint, ParseBigUintError> {
+        let mut limbs: Vec<u32> = Vec::new();
+        if s.is_empty() {
+            return Err(ParseBigUintError {
+                string: String::from(s),
+                valid_up_to: 0,
+            });
+        }
+        for (i, c) in s.chars().enumerate() {
+            let d = match c.to_digit(radix) {
+                Some(d) => d,
+                None => {
+                    return Err(ParseBigUintError {
+                        string: String::from(s),
+                        valid_up_to: i,
+                    })
+                }
+            };
+            let mut carry = u64::from(d);
+            for limb in limbs.iter_mut() {
+                let t = u64::from(*limb) * u64::from(radix) + carry;
+                *limb = t as u32;
+                carry = t >> 32;
+            }
+            if carry != 0 {
+                limbs.push(carry as u32);
+            }
+        }
+        Ok(BigUint::from_limbs(limbs))
+    }
+
+    /// A uniformly random integer in `[0, bound)`.
+    pub fn random_below(bound: &BigUint) -> BigUint {
+        assert!(!bound.is_zero(), "empty range");
+        let bits = bound.bits();
+        loop {
+            let mut bytes = ByteStream::random(bits.div_ceil(8)).into_bytes();
+            if !bits.is_multiple_of(8) {
+                bytes[0] &= (1 << (bits % 8)) - 1;
+            }
+            let n = BigUint::from_bytes_be(&bytes);
+            if n < *bound {
+                return n;
+            }
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(u64::from(self.limbs[0])),
+            2 => Some(u64::from(self.limbs[0]) | (u64::from(self.limbs[1]) << 32)),
+            _ => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.limbs.len() == 1 && self.limbs[0] == 1
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|l| l & 1 == 0)
+    }
+
+    /// The number of significant bits.
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 32 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn bit(&self, i: usize) -> bool {
+        match self.limbs.get(i / 32) {
+            Some(limb) => (limb >> (i % 32)) & 1 == 1,
+            None => false,
+        }
+    }
+
+    /// Returns the quotient and remainder of `self / divisor`.
+    pub fn divmod(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        assert!(!divisor.is_zero(), "division by zero");
+        if *self < *divisor {
+            return (BigUint::zero(), self.clone());
+        }
+        if divisor.limbs.len() == 1 {
+            let (q, r) = self.divmod_small(divisor.limbs[0]);
+            return (q, BigUint::from(u64::from(r)));
+        }
+        let (q, r) = divmod_knuth(&self.limbs, &divisor.limbs);
+        (BigUint::from_limbs(q), BigUint::from_limbs(r))
+    }
+
+    fn divmod_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut q = vec![0; self.limbs.len()];
+        let mut r = 0u64;
+        for i in (0..self.limbs.len()).rev() {
+            let t = (r << 32) | u64::from(self.limbs[i]);
+            q[i] = (t / u64::from(divisor)) as u32;
+            r = t % u64::from(divisor);
+        }
+        (BigUint::from_limbs(q), r as u32)
+    }
+
+    pub fn pow(&self, exp: u32) -> BigUint {
+        let mut result = BigUint::one();
+        for i in (0..32 - exp.leading_zeros()).rev() {
+            result = &result * &result;
+            if (exp >> i) & 1 == 1 {
+                result = &result * self;
+            }
+        }
+        result
+    }
+
+    /// Computes `self^exp mod modulus` by left-to-right square-and-multiply.
+    pub fn modpow(&self, exp: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero(), "division by zero");
+        if modulus.is_one() {
+            return BigUint::zero();
+        }
+        let base = self % modulus;
+        let mut result = BigUint::one();
+        for i in (0..exp.bits()).rev() {
+            result = &(&result * &result) % modulus;
+            if exp.bit(i) {
+                result = &(&result * &base) % modulus;
+            }
+        }
+        result
+    }
+
+    /// The inverse of `self` modulo `modulus`, if they are coprime.
+    pub fn modinv(&self, modulus: &BigUint) -> Option<BigUint> {
+        if modulus.is_zero() {
+            return None;
+        }
+        // Extended Euclid, keeping the coefficient of `self` reduced modulo
+        // `modulus` so that it never goes negative.
+        let (mut r0, mut r1) = (modulus.clone(), self % modulus);
+        let (mut t0, mut t1) = (BigUint::zero(), BigUint::one());
+        while !r1.is_zero() {
+            let (q, r2) = r0.divmod(&r1);
+            let qt = &(&q * &t1) % modulus;
+            let t2 = &(&t0 + modulus) - &qt;
+            let t2 = &t2 % modulus;
+            r0 = r1;
+            r1 = r2;
+            t0 = t1;
+            t1 = t2;
+        }
+        if r0.is_one() {
+            Some(t0)
+        } else {
+            None
+        }
+    }
+
+    pub fn gcd(&self, other: &BigUint) -> BigUint {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// The integer part of the `n`th root, by Newton's method from above.
+    pub fn nth_root(&self, n: u32) -> BigUint {
+        assert!(n > 0, "zeroth root");
+        if self.is_zero() || n == 1 {
+            return self.clone();
+        }
+        let n_big = BigUint::from(u64::from(n));
+        let mut x = BigUint::one() << self.bits().div_ceil(n as usize);
+        loop {
+            let y = (&x * &BigUint::from(u64::from(n - 1)) + self / &x.pow(n - 1)) / &n_big;
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// Trial division by the small primes followed by `rounds` rounds of
+    /// Miller-Rabin with random bases.
+    pub fn is_probable_prime(&self, rounds: usize) -> bool {
+        for &p in SMALL_PRIMES.iter() {
+            if *self == BigUint::from(u64::from(p)) {
+                return true;
+            }
+            if self.divmod_small(p).1 == 0 {
+                return false;
+            }
+        }
+        if *self < BigUint::from(2u64) {
+            return false;
+        }
+
+        let one = BigUint::one();
+        let n_minus_one = self - &one;
+        let s = (0..).find(|&i| n_minus_one.bit(i)).unwrap();
+        let d = &n_minus_one >> s;
+        let three = BigUint::from(3u64);
+        'witness: for _ in 0..rounds {
+            let a = BigUint::random_below(&(self - &three)) + BigUint::from(2u64);
+            let mut x = a.modpow(&d, self);
+            if x == one || x == n_minus_one {
+                continue;
+            }
+            for _ in 1..s {
+                x = &(&x * &x) % self;
+                if x == n_minus_one {
+                    continue 'witness;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// A random probable prime of exactly `bits` bits, with the top two bits
+    /// set so that the product of two such primes has exactly `2 * bits` bits.
+    pub fn random_prime(bits: usize) -> BigUint {
+        assert!(bits >= 2, "no primes of {} bits", bits);
+        let top = &(BigUint::one() << (bits - 1)) + &(BigUint::one() << (bits - 2));
+        loop {
+            let mut candidate = BigUint::random_below(&(BigUint::one() << (bits - 2))) + &top;
+            if candidate.is_even() {
+                candidate = candidate + BigUint::one();
+            }
+            if candidate.is_probable_prime(MILLER_RABIN_ROUNDS) {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// Solves `x = r_i mod m_i` for every `(r_i, m_i)` by the Chinese remainder
+/// theorem, returning the solution modulo the product of the `m_i`, or `None`
+/// if the moduli aren't pairwise coprime.
+pub fn crt(congruences: &[(BigUint, BigUint)]) -> Option<BigUint> {
+    let product = congruences
+        .iter()
+        .fold(BigUint::one(), |product, (_, m)| product * m);
+    let mut x = BigUint::zero();
+    for (r, m) in congruences {
+        let rest = &product / m;
+        let inverse = rest.modinv(m)?;
+        x = x + r * &rest * inverse;
+    }
+    Some(x % product)
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> BigUint {
+        BigUint::from_limbs(vec![n as u32, (n >> 32) as u32])
+    }
+}
+
+impl IntoByteStream for BigUint {
+    fn into_byte_stream(self) -> ByteStream {
+        self.to_byte_stream()
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_decimal())
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn add(a: &BigUint, b: &BigUint) -> BigUint {
+    let (long, short) = if a.limbs.len() >= b.limbs.len() {
+        (&a.limbs, &b.limbs)
+    } else {
+        (&b.limbs, &a.limbs)
+    };
+    let mut limbs = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, l) in long.iter().enumerate() {
+        let t = u64::from(*l) + u64::from(*short.get(i).unwrap_or(&0)) + carry;
+        limbs.push(t as u32);
+        carry = t >> 32;
+    }
+    limbs.push(carry as u32);
+    BigUint::from_limbs(limbs)
+}
+
+fn sub(a: &BigUint, b: &BigUint) -> BigUint {
+    assert!(a >= b, "subtraction underflow");
+    let mut limbs = Vec::with_capacity(a.limbs.len());
+    let mut borrow = 0i64;
+    for (i, l) in a.limbs.iter().enumerate() {
+        let t = i64::from(*l) - i64::from(*b.limbs.get(i).unwrap_or(&0)) - borrow;
+        limbs.push(t as u32);
+        borrow = if t < 0 { 1 } else { 0 };
+    }
+    BigUint::from_limbs(limbs)
+}
+
+fn mul(a: &BigUint, b: &BigUint) -> BigUint {
+    if a.is_zero() || b.is_zero() {
+        return BigUint::zero();
+    }
+    let mut limbs = vec![0u32; a.limbs.len() + b.limbs.len()];
+    for (i, x) in a.limbs.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.limbs.iter().enumerate() {
+            let t = u64::from(*x) * u64::from(*y) + u64::from(limbs[i + j]) + carry;
+            limbs[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        limbs[i + b.limbs.len()] = carry as u32;
+    }
+    BigUint::from_limbs(limbs)
+}
+
+fn shl_limbs(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = Vec::with_capacity(limbs.len() + 1);
+    let mut carry = 0;
+    for l in limbs {
+        shifted.push((l << shift) | carry);
+        carry = l >> (32 - shift);
+    }
+    shifted.push(carry);
+    shifted
+}
+
+fn shr_limbs(limbs: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return limbs.to_vec();
+    }
+    let mut shifted = vec![0; limbs.len()];
+    for i in 0..limbs.len() {
+        let high = limbs.get(i + 1).map_or(0, |h| h << (32 - shift));
+        shifted[i] = (limbs[i] >> shift) | high;
+    }
+    shifted
+}
+
+/// Long division of multi-limb numbers (Knuth, TAOCP vol. 2, algorithm D).
+/// Requires `v` to have at least two limbs and `u >= v`.
+fn divmod_knuth(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    const BASE: u64 = 1 << 32;
+    let n = v.len();
+    let m = u.len() - n;
+
+    // Normalize so that the divisor's top limb has its high bit set, which
+    // keeps each estimated quotient digit within two of the truth.
+    let s = v[n - 1].leading_zeros();
+    let vn = shl_limbs(v, s);
+    let mut un = shl_limbs(u, s);
+    if un.len() == u.len() {
+        un.push(0);
+    }
+
+    let mut q = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let num = (u64::from(un[j + n]) << 32) | u64::from(un[j + n - 1]);
+        let mut qhat = num / u64::from(vn[n - 1]);
+        let mut rhat = num % u64::from(vn[n - 1]);
+        while qhat >= BASE
+            || qhat * u64::from(vn[n - 2]) > ((rhat << 32) | u64::from(un[j + n - 2]))
+        {
+            qhat -= 1;
+            rhat += u64::from(vn[n - 1]);
+            if rhat >= BASE {
+                break;
+            }
+        }
+
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let p = qhat * u64::from(vn[i]) + carry;
+            carry = p >> 32;
+            let t = i64::from(un[i + j]) - borrow - (p & 0xffff_ffff) as i64;
+            un[i + j] = t as u32;
+            borrow = if t < 0 { 1 } else { 0 };
+        }
+        let t = i64::from(un[j + n]) - borrow - carry as i64;
+        un[j + n] = t as u32;
+
+        if t < 0 {
+            // The estimate was one too large; add the divisor back.
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let t = u64::from(un[i + j]) + u64::from(vn[i]) + carry;
+                un[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+        q[j] = qhat as u32;
+    }
+
+    (q, shr_limbs(&un[..n], s))
+}
+
+fn div(a: &BigUint, b: &BigUint) -> BigUint {
+    a.divmod(b).0
+}
+
+fn rem(a: &BigUint, b: &BigUint) -> BigUint {
+    a.divmod(b).1
+}
+
+macro_rules! forward_binop {
+    ($op:ident, $method:ident, $func:ident) => {
+        impl<'a, 'b> $op<&'b BigUint> for &'a BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: &BigUint) -> BigUint {
+                $func(self, other)
+            }
+        }
+
+        impl<'a> $op<&'a BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: &BigUint) -> BigUint {
+                $func(&self, other)
+            }
+        }
+
+        impl<'a> $op<BigUint> for &'a BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: BigUint) -> BigUint {
+                $func(self, &other)
+            }
+        }
+
+        impl $op<BigUint> for BigUint {
+            type Output = BigUint;
+
+            fn $method(self, other: BigUint) -> BigUint {
+                $func(&self, &other)
+            }
+        }
+    };
+}
+
+forward_binop!(Add, add, add);
+forward_binop!(Sub, sub, sub);
+forward_binop!(Mul, mul, mul);
+forward_binop!(Div, div, div);
+forward_binop!(Rem, rem, rem);
+
+impl Shl<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        let mut limbs = vec![0; shift / 32];
+        limbs.extend(shl_limbs(&self.limbs, (shift % 32) as u32));
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Shl<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        &self << shift
+    }
+}
+
+impl Shr<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: usize) -> BigUint {
+        if shift / 32 >= self.limbs.len() {
+            return BigUint::zero();
+        }
+        BigUint::from_limbs(shr_limbs(&self.limbs[shift / 32..], (shift % 32) as u32))
+    }
+}
+
+impl Shr<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: usize) -> BigUint {
+        &self >> shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> BigUint {
+        BigUint::from_decimal(s).unwrap()
+    }
+
+    #[test]
+    fn it_converts_between_representations() {
+        let n = BigUint::from_hex("0102030405060708090a0b0c0d0e0f").unwrap();
+        assert_eq!(n.to_hex(), "102030405060708090a0b0c0d0e0f");
+        assert_eq!(
+            n.to_byte_stream().into_hex(),
+            "0102030405060708090a0b0c0d0e0f"
+        );
+        assert_eq!(BigUint::from_byte_stream(&n.to_byte_stream_padded(20)), n);
+        assert_eq!(n.to_decimal(), "5233100606242806050955395731361295");
+        assert_eq!(BigUint::zero().to_hex(), "0");
+        assert_eq!(BigUint::zero().to_decimal(), "0");
+        assert!(BigUint::zero().to_byte_stream().is_empty());
+        assert!(BigUint::from_hex("12g4").is_err());
+        assert_eq!(
+            BigUint::from(0xdead_beef_cafe_u64).to_u64(),
+            Some(0xdead_beef_cafe)
+        );
+    }
+
+    #[test]
+    fn it_adds_and_subtracts() {
+        let a = dec("340282366920938463463374607431768211455");
+        let b = BigUint::one();
+        assert_eq!(&a + &b, BigUint::one() << 128);
+        assert_eq!((&a + &b) - &b, a);
+        assert_eq!(&a - &a, BigUint::zero());
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_on_underflow() {
+        let _ = BigUint::one() - BigUint::from(2u64);
+    }
+
+    #[test]
+    fn it_multiplies_and_divides() {
+        let a = dec("123456789012345678901234567890");
+        let b = dec("987654321098765432109876543210");
+        let product = &a * &b;
+        assert_eq!(
+            product.to_decimal(),
+            "121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!(&product / &a, b);
+        assert_eq!(&product % &a, BigUint::zero());
+
+        let (q, r) = (&product + BigUint::from(12345u64)).divmod(&b);
+        assert_eq!(q, a);
+        assert_eq!(r, BigUint::from(12345u64));
+
+        let n = dec("2").pow(521) - BigUint::one();
+        let d = dec("3").pow(100);
+        let (q, r) = n.divmod(&d);
+        assert!(r < d);
+        assert_eq!(&(&q * &d) + &r, n);
+    }
+
+    #[test]
+    fn it_computes_modular_arithmetic() {
+        assert_eq!(
+            BigUint::from(4u64).modpow(&BigUint::from(13u64), &BigUint::from(497u64)),
+            BigUint::from(445u64)
+        );
+        let p = (BigUint::one() << 127) - BigUint::one();
+        let a = dec("98765432109876543210");
+        assert_eq!(a.modpow(&(&p - &BigUint::one()), &p), BigUint::one());
+
+        assert_eq!(
+            BigUint::from(17u64).modinv(&BigUint::from(3120u64)),
+            Some(BigUint::from(2753u64))
+        );
+        assert_eq!(BigUint::from(6u64).modinv(&BigUint::from(9u64)), None);
+        let inv = a.modinv(&p).unwrap();
+        assert_eq!(&(&a * &inv) % &p, BigUint::one());
+
+        assert_eq!(
+            BigUint::from(1071u64).gcd(&BigUint::from(462u64)),
+            BigUint::from(21u64)
+        );
+    }
+
+    #[test]
+    fn it_compares() {
+        assert!(dec("100000000000000000000") > dec("99999999999999999999"));
+        assert!(BigUint::zero() < BigUint::one());
+        assert_eq!(dec("42").cmp(&BigUint::from(42u64)), Ordering::Equal);
+    }
+
+    #[test]
+    fn it_takes_roots() {
+        assert_eq!(dec("27").nth_root(3), dec("3"));
+        assert_eq!(dec("26").nth_root(3), dec("2"));
+        assert_eq!(BigUint::zero().nth_root(3), BigUint::zero());
+        assert_eq!(dec("1").nth_root(5), dec("1"));
+        let x = dec("123456789012345678901234567890");
+        assert_eq!(x.pow(3).nth_root(3), x);
+        assert_eq!((x.pow(3) - BigUint::one()).nth_root(3), &x - BigUint::one());
+        assert_eq!(x.pow(7).nth_root(7), x);
+    }
+
+    #[test]
+    fn it_solves_congruences() {
+        let congruences = [
+            (dec("2"), dec("3")),
+            (dec("3"), dec("5")),
+            (dec("2"), dec("7")),
+        ];
+        assert_eq!(crt(&congruences), Some(dec("23")));
+        assert_eq!(crt(&[(dec("1"), dec("4")), (dec("3"), dec("6"))]), None);
+    }
+
+    #[test]
+    fn it_tests_primality() {
+        for p in &["2", "251", "257", "170141183460469231731687303715884105727"] {
+            assert!(dec(p).is_probable_prime(20), "{}", p);
+        }
+        // 561 and 41041 are Carmichael numbers, which fool the Fermat test.
+        for c in &[
+            "0",
+            "1",
+            "561",
+            "41041",
+            "340282366920938463463374607431768211457",
+        ] {
+            assert!(!dec(c).is_probable_prime(20), "{}", c);
+        }
+    }
+
+    #[test]
+    fn it_generates_primes() {
+        for &bits in &[2, 16, 128] {
+            let p = BigUint::random_prime(bits);
+            assert_eq!(p.bits(), bits);
+            assert!(p.is_probable_prime(20));
+        }
+    }
+}
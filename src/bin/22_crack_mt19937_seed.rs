@@ -0,0 +1,27 @@
+//!
+//! ## Executable for: Set 3 - Problem 22
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./crack_mt19937_seed
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::mt19937::{crack_time_seed, unix_timestamp, Mt19937};
+
+fn main() {
+    // Rather than actually sleeping, pretend a random amount of time passed
+    // on either side of seeding the generator.
+    let mut clock = Mt19937::new(unix_timestamp());
+    let seed = unix_timestamp() + 40 + clock.extract() % 960;
+    let output = Mt19937::new(seed).extract();
+    let now = seed + 40 + clock.extract() % 960;
+
+    println!("Output: {}", output);
+    match crack_time_seed(output, now, 2000) {
+        Some(found) => println!("Seed: {} (actual: {})", found, seed),
+        None => println!("Seed not found"),
+    }
+}
@@ -0,0 +1,28 @@
+//!
+//! ## Executable for: Set 3 - Problem 24
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./mt19937_stream_cipher
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::mt19937::{keystream, mt19937_xor, recover_cipher_seed, unix_timestamp};
+
+fn main() {
+    let seed = unix_timestamp() as u16;
+    let known = ByteStream::from_ascii("AAAAAAAAAAAAAA").unwrap();
+
+    let prefix_len = 5 + (unix_timestamp() % 20) as usize;
+    let mut bs = keystream(unix_timestamp().rotate_left(7), prefix_len);
+    bs.append(&mut known.clone());
+    mt19937_xor(&mut bs, seed);
+
+    match recover_cipher_seed(&bs, &known) {
+        Some(found) => println!("Seed: {} (actual: {})", found, seed),
+        None => println!("Seed not found"),
+    }
+}
@@ -0,0 +1,23 @@
+//!
+//! ## Executable for: Set 4 - Problem 28
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./sha1_keyed_mac "YELLOW SUBMARINE" "attack at dawn"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::sha1::secret_prefix_mac;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        panic!("Not enough arguments");
+    }
+    let key = ByteStream::from_ascii(&args[1]).unwrap();
+    let message = ByteStream::from_ascii(&args[2]).unwrap();
+    println!("{}", secret_prefix_mac(&key, &message).into_hex());
+}
@@ -0,0 +1,33 @@
+//!
+//! ## Executable for: Set 4 - Problem 29
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./sha1_length_extension
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::length_extension::{attack, Oracle};
+use cryptopals::sha1::Sha1;
+
+fn main() {
+    let oracle = Oracle::<Sha1>::new(ByteStream::from_ascii("a secret nobody knows").unwrap());
+    let message = ByteStream::from_ascii(
+        "comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon",
+    )
+    .unwrap();
+    let mac = oracle.sign(&message);
+    let extension = ByteStream::from_ascii(";admin=true").unwrap();
+
+    match attack(&oracle, &message, &mac, &extension, 0..64) {
+        Some(forgery) => {
+            println!("Secret length: {}", forgery.secret_len);
+            println!("Message: {}", forgery.message.into_hex());
+            println!("MAC: {}", forgery.mac.into_hex());
+        }
+        None => println!("No forgery found"),
+    }
+}
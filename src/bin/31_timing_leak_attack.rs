@@ -0,0 +1,27 @@
+//!
+//! ## Executable for: Set 4 - Problems 31 and 32
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./timing_leak_attack 127.0.0.1:9000 foo 5
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::timing_leak::{recover_signature, request};
+use std::net::SocketAddr;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 4 {
+        panic!("Not enough arguments");
+    }
+    let addr: SocketAddr = args[1].parse().unwrap();
+    let rounds = args[3].parse().unwrap();
+
+    let signature = recover_signature(&addr, &args[2], 20, rounds).unwrap();
+    let (accepted, _) = request(&addr, &args[2], &signature).unwrap();
+    println!("Signature: {}", signature.into_hex());
+    println!("Accepted: {}", accepted);
+}
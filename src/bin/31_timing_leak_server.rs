@@ -0,0 +1,25 @@
+//!
+//! ## Executable for: Set 4 - Problems 31 and 32
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./timing_leak_server 127.0.0.1:9000 50
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::timing_leak::Server;
+use std::time::Duration;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        panic!("Not enough arguments");
+    }
+    let delay = Duration::from_millis(args[2].parse().unwrap());
+    let server = Server::bind(&args[1], ByteStream::random(16), delay).unwrap();
+    println!("Listening on {}", server.local_addr().unwrap());
+    server.serve().unwrap();
+}
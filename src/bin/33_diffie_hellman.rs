@@ -0,0 +1,29 @@
+//!
+//! ## Executable for: Set 5 - Problem 33
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./diffie_hellman 1536
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::dh::{derive_key, Group};
+use cryptopals::sha2::Sha256;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let group = Group::modp(args[1].parse().unwrap()).expect("no MODP group of that size");
+
+    let alice = group.generate_keypair();
+    let bob = group.generate_keypair();
+    let alice_secret = alice.shared_secret(&group, &bob.public);
+    let bob_secret = bob.shared_secret(&group, &alice.public);
+
+    println!("Secrets match: {}", alice_secret == bob_secret);
+    println!("Key: {}", derive_key::<Sha256>(&alice_secret).into_hex());
+}
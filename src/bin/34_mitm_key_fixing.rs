@@ -0,0 +1,31 @@
+//!
+//! ## Executable for: Set 5 - Problems 34 and 35
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./mitm_key_fixing "attack at dawn"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::dh::Group;
+use cryptopals::mitm::{run, Channel, EchoClient, EchoServer, KeyFixing};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let message = ByteStream::from_ascii(&args[1]).unwrap();
+
+    let mut client = EchoClient::new(Group::modp(1536).unwrap(), message);
+    let mut server = EchoServer::new();
+    let mut channel = Channel::new(KeyFixing::new());
+    run(&mut client, &mut server, &mut channel, 100);
+
+    for plaintext in channel.interceptor().recovered.iter() {
+        println!("Intercepted: {}", plaintext.clone().into_hex());
+    }
+}
@@ -0,0 +1,38 @@
+//!
+//! ## Executable for: Set 5 - Problems 36 and 37
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./secure_remote_password "correct horse" "battery staple"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::srp::{login, zero_key_login, Client, Listener, Parameters, Server};
+
+const EMAIL: &str = "alice@example.com";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        panic!("Not enough arguments");
+    }
+    let params = Parameters::modp_1536();
+
+    let mut server = Server::without_public_key_check(params.clone());
+    server.register(EMAIL, &args[1]);
+    let listener = Listener::bind("127.0.0.1:0", server).unwrap();
+    let addr = listener.local_addr().unwrap();
+    listener.spawn();
+
+    let client = Client::new(params.clone(), EMAIL, &args[2]);
+    println!("Password login: {}", login(&addr, &client).unwrap());
+    for multiple in 0..3 {
+        println!(
+            "Zero-key login with A = {}N: {}",
+            multiple,
+            zero_key_login(&addr, &params, EMAIL, multiple).unwrap()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//!
+//! ## Executable for: Set 5 - Problem 38
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./srp_dictionary_attack sunshine password 123456 letmein sunshine qwerty
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::srp::{MaliciousSimpleServer, Parameters, SimpleClient};
+
+const EMAIL: &str = "alice@example.com";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        panic!("Not enough arguments");
+    }
+    let params = Parameters::modp_1536();
+
+    let server = MaliciousSimpleServer::new(params.clone());
+    let client = SimpleClient::new(params, EMAIL, &args[1]);
+    let proof = client.respond(&server.challenge());
+
+    let dictionary = args[2..].iter().map(String::as_str);
+    match server.crack(EMAIL, client.public(), &proof, dictionary) {
+        Some(password) => println!("Password: {}", password),
+        None => println!("Password not in dictionary"),
+    }
+}
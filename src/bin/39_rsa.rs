@@ -0,0 +1,36 @@
+//!
+//! ## Executable for: Set 5 - Problem 39
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./rsa 1024 "hi mom"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::rsa::{Keypair, DEFAULT_EXPONENT};
+use cryptopals::sha2::Sha256;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        panic!("Not enough arguments");
+    }
+    let keypair = Keypair::generate(args[1].parse().unwrap(), DEFAULT_EXPONENT);
+    let message = ByteStream::from_ascii(&args[2]).unwrap();
+
+    let ciphertext = keypair.public.encrypt_pkcs1(&message).unwrap();
+    let plaintext = keypair.private.decrypt_pkcs1(&ciphertext).unwrap();
+    let signature = keypair.private.sign_pkcs1::<Sha256>(&message).unwrap();
+
+    println!("n: {}", keypair.public.n.to_hex());
+    println!("Ciphertext: {}", ciphertext.into_hex());
+    println!("Decrypted: {}", plaintext.into_hex());
+    println!("Signature: {}", signature.clone().into_hex());
+    println!(
+        "Signature valid: {}",
+        keypair.public.verify_pkcs1::<Sha256>(&message, &signature)
+    );
+}
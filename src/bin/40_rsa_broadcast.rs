@@ -0,0 +1,35 @@
+//!
+//! ## Executable for: Set 5 - Problem 40
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./rsa_broadcast "attack at dawn"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::rsa::Keypair;
+use cryptopals::rsa_broadcast::broadcast_attack;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let message = ByteStream::from_ascii(&args[1]).unwrap();
+
+    let ciphertexts: Vec<_> = (0..3)
+        .map(|_| {
+            let key = Keypair::generate(1024, 3).public;
+            let c = key.encrypt(&message).unwrap();
+            (key, c)
+        })
+        .collect();
+
+    match broadcast_attack(&ciphertexts) {
+        Some(m) => println!("Recovered: {}", m.into_hex()),
+        None => println!("Recovery failed"),
+    }
+}
@@ -0,0 +1,32 @@
+//!
+//! ## Executable for: Set 6 - Problem 41
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./rsa_unpadded_oracle "{time: 1356304276, social: '555-55-5555'}"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::rsa::{Keypair, DEFAULT_EXPONENT};
+use cryptopals::rsa_unpadded::{recover_message, Oracle};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let message = ByteStream::from_ascii(&args[1]).unwrap();
+
+    let mut oracle = Oracle::new(Keypair::generate(1024, DEFAULT_EXPONENT));
+    let ciphertext = oracle.public_key().encrypt(&message).unwrap();
+    oracle.decrypt(&ciphertext);
+
+    println!("Oracle refuses: {}", oracle.decrypt(&ciphertext).is_none());
+    match recover_message(&mut oracle, &ciphertext) {
+        Some(m) => println!("Recovered: {}", m.into_hex()),
+        None => println!("Recovery failed"),
+    }
+}
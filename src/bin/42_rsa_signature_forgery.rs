@@ -0,0 +1,35 @@
+//!
+//! ## Executable for: Set 6 - Problem 42
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./rsa_signature_forgery "hi mom"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::rsa::Keypair;
+use cryptopals::rsa_signature_forgery::{forge_signature, verify_lax};
+use cryptopals::sha1::Sha1;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let message = ByteStream::from_ascii(&args[1]).unwrap();
+    let key = Keypair::generate(1024, 3).public;
+
+    let forged = forge_signature::<Sha1>(&key, &message).expect("key too small to forge");
+    println!("Forged signature: {}", forged.clone().into_hex());
+    println!(
+        "Lax verifier accepts: {}",
+        verify_lax::<Sha1>(&key, &message, &forged)
+    );
+    println!(
+        "Strict verifier accepts: {}",
+        key.verify_pkcs1::<Sha1>(&message, &forged)
+    );
+}
@@ -0,0 +1,64 @@
+//!
+//! ## Executable for: Set 6 - Problems 43, 44 and 45
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./dsa_nonce_recovery "hi mom"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::bignum::BigUint;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::dsa::{
+    find_repeated_nonce, forge_with_one_generator, recover_private_key_brute_force, Parameters,
+};
+use cryptopals::sha1::Sha1;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let message = ByteStream::from_ascii(&args[1]).unwrap();
+    let params = Parameters::default_1024();
+    let keypair = params.generate_keypair();
+    println!("Private key: {}", keypair.private.to_hex());
+
+    // A nonce of zero can't sign, so draw from 1..2^16.
+    let k = BigUint::random_below(&BigUint::from((1 << 16) - 1)) + BigUint::one();
+    let signature = keypair
+        .sign_with_nonce::<Sha1>(&params, &message, &k)
+        .unwrap();
+    let recovered = recover_private_key_brute_force::<Sha1>(
+        &params,
+        &keypair.public,
+        &message,
+        &signature,
+        1..1 << 16,
+    );
+    println!("From a 16-bit nonce: {:?}", recovered.map(|x| x.to_hex()));
+
+    let signed: Vec<_> = ["first", "second"]
+        .iter()
+        .map(|m| {
+            let m = ByteStream::from_ascii(m).unwrap();
+            let signature = keypair.sign_with_nonce::<Sha1>(&params, &m, &k).unwrap();
+            (m, signature)
+        })
+        .collect();
+    let recovered = find_repeated_nonce::<Sha1>(&params, &keypair.public, &signed);
+    println!("From a repeated nonce: {:?}", recovered.map(|x| x.to_hex()));
+
+    let tampered = Parameters::new(
+        params.p.clone(),
+        params.q.clone(),
+        &params.p + BigUint::one(),
+    );
+    let forged = forge_with_one_generator(&tampered, &keypair.public);
+    println!(
+        "Forgery with g = p + 1 verifies: {}",
+        tampered.verify::<Sha1>(&keypair.public, &message, &forged)
+    );
+}
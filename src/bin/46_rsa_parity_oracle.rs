@@ -0,0 +1,34 @@
+//!
+//! ## Executable for: Set 6 - Problem 46
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./rsa_parity_oracle "That's why I found you don't play around with the Funky Cold Medina"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::rsa::{Keypair, DEFAULT_EXPONENT};
+use cryptopals::rsa_parity::{recover_plaintext_with_progress, Oracle};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let message = ByteStream::from_ascii(&args[1]).unwrap();
+
+    let oracle = Oracle::new(Keypair::generate(1024, DEFAULT_EXPONENT));
+    let ciphertext = oracle.public_key().encrypt(&message).unwrap();
+
+    let recovered = recover_plaintext_with_progress(&oracle, &ciphertext, |bound| {
+        let bytes = bound.to_bytes_be();
+        println!("{}", String::from_utf8_lossy(&bytes).escape_debug());
+    });
+    println!(
+        "Recovered: {}",
+        String::from_utf8_lossy(&recovered.into_bytes())
+    );
+}
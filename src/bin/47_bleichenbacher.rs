@@ -0,0 +1,33 @@
+//!
+//! ## Executable for: Set 6 - Problems 47 and 48
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./bleichenbacher 768 "kick it, CC"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::bleichenbacher::{recover_plaintext, Oracle};
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::rsa::{Keypair, DEFAULT_EXPONENT};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        panic!("Not enough arguments");
+    }
+    let oracle = Oracle::new(Keypair::generate(
+        args[1].parse().unwrap(),
+        DEFAULT_EXPONENT,
+    ));
+    let message = ByteStream::from_ascii(&args[2]).unwrap();
+    let ciphertext = oracle.public_key().encrypt_pkcs1(&message).unwrap();
+
+    match recover_plaintext(&oracle, &ciphertext) {
+        Some(m) => println!("Recovered: {}", String::from_utf8_lossy(&m.into_bytes())),
+        None => println!("Recovery failed"),
+    }
+    println!("Oracle queries: {}", oracle.queries());
+}
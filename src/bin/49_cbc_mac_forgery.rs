@@ -0,0 +1,41 @@
+//!
+//! ## Executable for: Set 7 - Problem 49
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./cbc_mac_forgery
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::cbc_mac::{cbc_mac, extend, forge_first_block, verify};
+
+fn main() {
+    let key = ByteStream::random(16);
+    let iv = ByteStream::random(16);
+    let own = ByteStream::from_ascii("from=1337&to=1337&amount=1000000").unwrap();
+    let mac = cbc_mac(&key, &iv, &own);
+    let (forged, forged_iv) = forge_first_block(
+        &own,
+        &iv,
+        &ByteStream::from_ascii("from=0042&to=133").unwrap(),
+    )
+    .unwrap();
+    println!(
+        "Chosen IV forgery accepted: {}",
+        verify(&key, &forged_iv, &forged, &mac)
+    );
+
+    let zero_iv = ByteStream::from(vec![0; 16]);
+    let victim = ByteStream::from_ascii("from=0042&tx_list=0007:5000;0099:25").unwrap();
+    let victim_mac = cbc_mac(&key, &zero_iv, &victim);
+    let extension = ByteStream::from_ascii("from=1337&tx_list=1337:1000000").unwrap();
+    let extension_mac = cbc_mac(&key, &zero_iv, &extension);
+    let forged = extend(&victim, &victim_mac, &extension).unwrap();
+    println!(
+        "Length extension accepted: {}",
+        verify(&key, &zero_iv, &forged, &extension_mac)
+    );
+}
@@ -0,0 +1,26 @@
+//!
+//! ## Executable for: Set 7 - Problem 50
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./cbc_mac_hash_collision "alert('Ayo, the Wu is back!');"
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::cbc_mac::{forge_javascript, hash};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let original = ByteStream::from_ascii("alert('MZA who was that?');\n").unwrap();
+    let forged = forge_javascript(&original, &args[1]).unwrap();
+
+    println!("Original hash: {}", hash(&original).into_hex());
+    println!("Forged hash:   {}", hash(&forged).into_hex());
+    println!("Forged snippet (hex): {}", forged.into_hex());
+}
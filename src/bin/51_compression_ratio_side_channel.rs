@@ -0,0 +1,26 @@
+//!
+//! ## Executable for: Set 7 - Problem 51
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./compression_ratio_side_channel TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::compression_oracle::{recover_session_id, Mode, Oracle};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    for mode in &[Mode::Ctr, Mode::Cbc] {
+        let oracle = Oracle::new(*mode, &args[1]);
+        match recover_session_id(&oracle) {
+            Some(session_id) => println!("{:?}: recovered {}", mode, session_id),
+            None => println!("{:?}: recovery failed", mode),
+        }
+    }
+}
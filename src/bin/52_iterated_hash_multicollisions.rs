@@ -0,0 +1,34 @@
+//!
+//! ## Executable for: Set 7 - Problem 52
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./iterated_hash_multicollisions
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::multicollision::cascade_collision;
+use cryptopals::toy_hash::ToyHash;
+
+fn main() {
+    let (cheap, expensive) = (ToyHash::new(2), ToyHash::new(4));
+    let (first, second) = cascade_collision(&cheap, &expensive);
+
+    println!("First:  {}", first.clone().into_hex());
+    println!("Second: {}", second.clone().into_hex());
+    println!(
+        "Digests: {}{} and {}{}",
+        cheap.digest(&first).into_hex(),
+        expensive.digest(&first).into_hex(),
+        cheap.digest(&second).into_hex(),
+        expensive.digest(&second).into_hex()
+    );
+    println!(
+        "Compression calls: {} cheap, {} expensive (birthday bound 2^{})",
+        cheap.calls(),
+        expensive.calls(),
+        expensive.bits() / 2
+    );
+}
@@ -0,0 +1,34 @@
+//!
+//! ## Executable for: Set 7 - Problem 53
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./kelsey_schneier_second_preimage 12
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::expandable_message::second_preimage;
+use cryptopals::toy_hash::{ToyHash, BLOCK_SIZE};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let k: usize = args[1].parse().expect("k must be a number");
+    let hash = ToyHash::new(3);
+    let message = ByteStream::random(BLOCK_SIZE << k);
+
+    match second_preimage(&hash, &message) {
+        Some(forged) => {
+            println!("Target digest: {}", hash.digest(&message).into_hex());
+            println!("Forged digest: {}", hash.digest(&forged).into_hex());
+            println!("Same message: {}", forged == message);
+        }
+        None => println!("No second preimage found"),
+    }
+    println!("Compression calls: {}", hash.calls());
+}
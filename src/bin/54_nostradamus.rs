@@ -0,0 +1,55 @@
+//!
+//! ## Executable for: Set 7 - Problem 54
+//!
+//! # Examples
+//!
+//! ```shell
+//! ./nostradamus "Yankees 4, Red Sox 3, in 11 inn."
+//! ```
+//!
+
+extern crate cryptopals;
+use cryptopals::byte_stream::ByteStream;
+use cryptopals::herding::{expected_work, herd, kelsey_kohno_bound, Diamond};
+use cryptopals::toy_hash::{ToyHash, BLOCK_SIZE};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        panic!("Not enough arguments");
+    }
+    let mut prefix = ByteStream::from_ascii(&args[1]).unwrap();
+    while !prefix.len().is_multiple_of(BLOCK_SIZE) {
+        prefix.push(b' ');
+    }
+
+    println!("Compressions for a 24-bit hash: measured, this construction's");
+    println!("expectation, and Kelsey and Kohno's bound.");
+    println!(
+        "{:>3}  {:>9} {:>9} {:>9}  {:>9} {:>9}  {:>9} {:>9}",
+        "k", "diamond", "expected", "bound", "link", "bound", "total", "bound"
+    );
+    for k in (2..=12).step_by(2) {
+        let hash = ToyHash::new(3);
+        let diamond = Diamond::generate(&hash, k);
+        let commitment = diamond.commitment(&hash, prefix.len() / BLOCK_SIZE);
+        let built = hash.calls();
+        let message = herd(&hash, &diamond, &commitment, &prefix).expect("no linking block found");
+        let linked = hash.calls() - built;
+        assert_eq!(hash.digest(&message), commitment.digest);
+
+        let (diamond_work, _) = expected_work(hash.bits(), k);
+        let (diamond_bound, link_bound) = kelsey_kohno_bound(hash.bits(), k);
+        println!(
+            "{:>3}  {:>9} {:>9.0} {:>9.0}  {:>9} {:>9.0}  {:>9} {:>9.0}",
+            k,
+            built,
+            diamond_work,
+            diamond_bound,
+            linked,
+            link_bound,
+            built + linked,
+            diamond_bound + link_bound
+        );
+    }
+}
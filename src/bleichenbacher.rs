@@ -0,0 +1,198 @@
+//!
+//! Bleichenbacher's 1998 adaptive chosen-ciphertext attack, which decrypts
+//! PKCS#1 v1.5 ciphertexts given an oracle that says whether a ciphertext
+//! decrypts to a block starting with `00 02`.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use rsa::{pkcs1_encryption_unpad, Keypair, PublicKey};
+use std::cell::Cell;
+
+pub struct Oracle {
+    keypair: Keypair,
+    queries: Cell<usize>,
+}
+
+impl Oracle {
+    pub fn new(keypair: Keypair) -> Oracle {
+        Oracle {
+            keypair,
+            queries: Cell::new(0),
+        }
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.keypair.public
+    }
+
+    /// Whether `ciphertext` decrypts to a block starting with `00 02`.
+    pub fn is_conforming(&self, ciphertext: &BigUint) -> bool {
+        self.queries.set(self.queries.get() + 1);
+        let m = self.keypair.private.decrypt_raw(ciphertext);
+        let b = bound(self.keypair.private.size());
+        m >= &b * BigUint::from(2u64) && m < &b * BigUint::from(3u64)
+    }
+
+    /// How many times the oracle has been consulted.
+    pub fn queries(&self) -> usize {
+        self.queries.get()
+    }
+}
+
+/// `B = 2^(8(k - 2))`, so that conforming blocks lie in `[2B, 3B)`.
+fn bound(k: usize) -> BigUint {
+    BigUint::one() << (8 * (k - 2))
+}
+
+fn div_ceil(a: &BigUint, b: &BigUint) -> BigUint {
+    let (q, r) = a.divmod(b);
+    if r.is_zero() {
+        q
+    } else {
+        q + BigUint::one()
+    }
+}
+
+/// Adds `[a, b]` to a list of disjoint intervals, merging any it overlaps.
+fn insert_interval(intervals: &mut Vec<(BigUint, BigUint)>, a: BigUint, b: BigUint) {
+    let (mut a, mut b) = (a, b);
+    intervals.retain(|(x, y)| {
+        if *x <= b && a <= *y {
+            a = a.clone().min(x.clone());
+            b = b.clone().max(y.clone());
+            false
+        } else {
+            true
+        }
+    });
+    intervals.push((a, b));
+}
+
+/// Recovers the message behind a PKCS#1 v1.5 ciphertext, or returns `None`
+/// if the recovered block turns out not to be properly padded.
+pub fn recover_plaintext(oracle: &Oracle, ciphertext: &ByteStream) -> Option<ByteStream> {
+    let key = oracle.public_key();
+    let n = &key.n;
+    let k = key.size();
+    let b = bound(k);
+    let two_b = &b * BigUint::from(2u64);
+    let three_b = &b * BigUint::from(3u64);
+    let one = BigUint::one();
+    let times = |c: &BigUint, s: &BigUint| c * key.encrypt_raw(s) % n;
+
+    // Step 1: blind the ciphertext until it conforms. A ciphertext produced by
+    // a PKCS#1 v1.5 encryption already does.
+    let c = BigUint::from_byte_stream(ciphertext);
+    let (c0, s0) = if oracle.is_conforming(&c) {
+        (c, one.clone())
+    } else {
+        loop {
+            let s0 = BigUint::random_below(&(n - &one)) + &one;
+            let c0 = times(&c, &s0);
+            if oracle.is_conforming(&c0) {
+                break (c0, s0);
+            }
+        }
+    };
+
+    let mut intervals = vec![(two_b.clone(), &three_b - &one)];
+    let mut s = div_ceil(n, &three_b);
+    let mut first = true;
+    loop {
+        if first || intervals.len() > 1 {
+            // Steps 2a and 2b: search upwards for the next conforming `s`.
+            if !first {
+                s = s + &one;
+            }
+            while !oracle.is_conforming(&times(&c0, &s)) {
+                s = s + &one;
+            }
+            first = false;
+        } else {
+            // Step 2c: with a single interval left, search `r` and `s`
+            // together, roughly halving the interval each time.
+            let (ref a, ref upper) = intervals[0];
+            let mut r = div_ceil(&((upper * &s - &two_b) * BigUint::from(2u64)), n);
+            s = 'search: loop {
+                let low = div_ceil(&(&two_b + &r * n), upper);
+                let high = (&three_b + &r * n) / a;
+                let mut candidate = low;
+                while candidate <= high {
+                    if oracle.is_conforming(&times(&c0, &candidate)) {
+                        break 'search candidate;
+                    }
+                    candidate = candidate + &one;
+                }
+                r = r + &one;
+            };
+        }
+
+        // Step 3: narrow every interval to the values consistent with
+        // `m * s` conforming.
+        let mut narrowed = Vec::new();
+        for (a, upper) in &intervals {
+            let a_s = a * &s;
+            let mut r = if &a_s + &one > three_b {
+                div_ceil(&(&a_s + &one - &three_b), n)
+            } else {
+                BigUint::zero()
+            };
+            let r_max = (upper * &s - &two_b) / n;
+            while r <= r_max {
+                let lo = div_ceil(&(&two_b + &r * n), &s).max(a.clone());
+                let hi = ((&three_b - &one + &r * n) / &s).min(upper.clone());
+                if lo <= hi {
+                    insert_interval(&mut narrowed, lo, hi);
+                }
+                r = r + &one;
+            }
+        }
+        intervals = narrowed;
+        if intervals.is_empty() {
+            return None;
+        }
+
+        // Step 4: stop once a single value is left.
+        if intervals.len() == 1 && intervals[0].0 == intervals[0].1 {
+            let m = &intervals[0].0 * s0.modinv(n)? % n;
+            return pkcs1_encryption_unpad(&m.to_byte_stream_padded(k)).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::DEFAULT_EXPONENT;
+
+    fn recovers(bits: usize) {
+        let oracle = Oracle::new(Keypair::generate(bits, DEFAULT_EXPONENT));
+        let message = ByteStream::from_ascii("kick it, CC").unwrap();
+        let ciphertext = oracle.public_key().encrypt_pkcs1(&message).unwrap();
+
+        assert!(oracle.is_conforming(&BigUint::from_byte_stream(&ciphertext)));
+        assert_eq!(recover_plaintext(&oracle, &ciphertext), Some(message));
+    }
+
+    #[test]
+    fn it_recovers_the_plaintext_under_a_256_bit_key() {
+        recovers(256);
+    }
+
+    #[test]
+    fn it_recovers_the_plaintext_under_a_768_bit_key() {
+        recovers(768);
+    }
+
+    #[test]
+    fn it_rejects_nonconforming_ciphertexts() {
+        let oracle = Oracle::new(Keypair::generate(256, DEFAULT_EXPONENT));
+        let ciphertext = oracle
+            .public_key()
+            .encrypt(&ByteStream::from_ascii("kick it, CC").unwrap())
+            .unwrap();
+        assert!(!oracle.is_conforming(&BigUint::from_byte_stream(&ciphertext)));
+        assert_eq!(oracle.queries(), 1);
+    }
+}
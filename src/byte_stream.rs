@@ -2,6 +2,9 @@
 
 use std::f64;
 use std::fmt;
+use std::fs::File;
+use std::hint;
+use std::io::Read;
 use std::slice::Chunks;
 use std::slice::ChunksMut;
 use std::slice::Iter;
@@ -47,6 +50,15 @@ impl fmt::Display for FromAsciiError {
     }
 }
 
+#[derive(Debug)]
+pub struct PaddingError {}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid PKCS#7 padding")
+    }
+}
+
 #[derive(PartialOrd, Eq, Ord, PartialEq, Debug)]
 pub struct ByteStream {
     data: Vec<u8>,
@@ -56,6 +68,44 @@ pub trait IntoByteStream {
     fn into_byte_stream(self) -> ByteStream;
 }
 
+const GALOIS_MUL_2: [u8; 256] = [
+    0x00, 0x02, 0x04, 0x06, 0x08, 0x0a, 0x0c, 0x0e, 0x10, 0x12, 0x14, 0x16, 0x18, 0x1a, 0x1c, 0x1e,
+    0x20, 0x22, 0x24, 0x26, 0x28, 0x2a, 0x2c, 0x2e, 0x30, 0x32, 0x34, 0x36, 0x38, 0x3a, 0x3c, 0x3e,
+    0x40, 0x42, 0x44, 0x46, 0x48, 0x4a, 0x4c, 0x4e, 0x50, 0x52, 0x54, 0x56, 0x58, 0x5a, 0x5c, 0x5e,
+    0x60, 0x62, 0x64, 0x66, 0x68, 0x6a, 0x6c, 0x6e, 0x70, 0x72, 0x74, 0x76, 0x78, 0x7a, 0x7c, 0x7e,
+    0x80, 0x82, 0x84, 0x86, 0x88, 0x8a, 0x8c, 0x8e, 0x90, 0x92, 0x94, 0x96, 0x98, 0x9a, 0x9c, 0x9e,
+    0xa0, 0xa2, 0xa4, 0xa6, 0xa8, 0xaa, 0xac, 0xae, 0xb0, 0xb2, 0xb4, 0xb6, 0xb8, 0xba, 0xbc, 0xbe,
+    0xc0, 0xc2, 0xc4, 0xc6, 0xc8, 0xca, 0xcc, 0xce, 0xd0, 0xd2, 0xd4, 0xd6, 0xd8, 0xda, 0xdc, 0xde,
+    0xe0, 0xe2, 0xe4, 0xe6, 0xe8, 0xea, 0xec, 0xee, 0xf0, 0xf2, 0xf4, 0xf6, 0xf8, 0xfa, 0xfc, 0xfe,
+    0x1b, 0x19, 0x1f, 0x1d, 0x13, 0x11, 0x17, 0x15, 0x0b, 0x09, 0x0f, 0x0d, 0x03, 0x01, 0x07, 0x05,
+    0x3b, 0x39, 0x3f, 0x3d, 0x33, 0x31, 0x37, 0x35, 0x2b, 0x29, 0x2f, 0x2d, 0x23, 0x21, 0x27, 0x25,
+    0x5b, 0x59, 0x5f, 0x5d, 0x53, 0x51, 0x57, 0x55, 0x4b, 0x49, 0x4f, 0x4d, 0x43, 0x41, 0x47, 0x45,
+    0x7b, 0x79, 0x7f, 0x7d, 0x73, 0x71, 0x77, 0x75, 0x6b, 0x69, 0x6f, 0x6d, 0x63, 0x61, 0x67, 0x65,
+    0x9b, 0x99, 0x9f, 0x9d, 0x93, 0x91, 0x97, 0x95, 0x8b, 0x89, 0x8f, 0x8d, 0x83, 0x81, 0x87, 0x85,
+    0xbb, 0xb9, 0xbf, 0xbd, 0xb3, 0xb1, 0xb7, 0xb5, 0xab, 0xa9, 0xaf, 0xad, 0xa3, 0xa1, 0xa7, 0xa5,
+    0xdb, 0xd9, 0xdf, 0xdd, 0xd3, 0xd1, 0xd7, 0xd5, 0xcb, 0xc9, 0xcf, 0xcd, 0xc3, 0xc1, 0xc7, 0xc5,
+    0xfb, 0xf9, 0xff, 0xfd, 0xf3, 0xf1, 0xf7, 0xf5, 0xeb, 0xe9, 0xef, 0xed, 0xe3, 0xe1, 0xe7, 0xe5,
+];
+
+const GALOIS_MUL_3: [u8; 256] = [
+    0x00, 0x03, 0x06, 0x05, 0x0c, 0x0f, 0x0a, 0x09, 0x18, 0x1b, 0x1e, 0x1d, 0x14, 0x17, 0x12, 0x11,
+    0x30, 0x33, 0x36, 0x35, 0x3c, 0x3f, 0x3a, 0x39, 0x28, 0x2b, 0x2e, 0x2d, 0x24, 0x27, 0x22, 0x21,
+    0x60, 0x63, 0x66, 0x65, 0x6c, 0x6f, 0x6a, 0x69, 0x78, 0x7b, 0x7e, 0x7d, 0x74, 0x77, 0x72, 0x71,
+    0x50, 0x53, 0x56, 0x55, 0x5c, 0x5f, 0x5a, 0x59, 0x48, 0x4b, 0x4e, 0x4d, 0x44, 0x47, 0x42, 0x41,
+    0xc0, 0xc3, 0xc6, 0xc5, 0xcc, 0xcf, 0xca, 0xc9, 0xd8, 0xdb, 0xde, 0xdd, 0xd4, 0xd7, 0xd2, 0xd1,
+    0xf0, 0xf3, 0xf6, 0xf5, 0xfc, 0xff, 0xfa, 0xf9, 0xe8, 0xeb, 0xee, 0xed, 0xe4, 0xe7, 0xe2, 0xe1,
+    0xa0, 0xa3, 0xa6, 0xa5, 0xac, 0xaf, 0xaa, 0xa9, 0xb8, 0xbb, 0xbe, 0xbd, 0xb4, 0xb7, 0xb2, 0xb1,
+    0x90, 0x93, 0x96, 0x95, 0x9c, 0x9f, 0x9a, 0x99, 0x88, 0x8b, 0x8e, 0x8d, 0x84, 0x87, 0x82, 0x81,
+    0x9b, 0x98, 0x9d, 0x9e, 0x97, 0x94, 0x91, 0x92, 0x83, 0x80, 0x85, 0x86, 0x8f, 0x8c, 0x89, 0x8a,
+    0xab, 0xa8, 0xad, 0xae, 0xa7, 0xa4, 0xa1, 0xa2, 0xb3, 0xb0, 0xb5, 0xb6, 0xbf, 0xbc, 0xb9, 0xba,
+    0xfb, 0xf8, 0xfd, 0xfe, 0xf7, 0xf4, 0xf1, 0xf2, 0xe3, 0xe0, 0xe5, 0xe6, 0xef, 0xec, 0xe9, 0xea,
+    0xcb, 0xc8, 0xcd, 0xce, 0xc7, 0xc4, 0xc1, 0xc2, 0xd3, 0xd0, 0xd5, 0xd6, 0xdf, 0xdc, 0xd9, 0xda,
+    0x5b, 0x58, 0x5d, 0x5e, 0x57, 0x54, 0x51, 0x52, 0x43, 0x40, 0x45, 0x46, 0x4f, 0x4c, 0x49, 0x4a,
+    0x6b, 0x68, 0x6d, 0x6e, 0x67, 0x64, 0x61, 0x62, 0x73, 0x70, 0x75, 0x76, 0x7f, 0x7c, 0x79, 0x7a,
+    0x3b, 0x38, 0x3d, 0x3e, 0x37, 0x34, 0x31, 0x32, 0x23, 0x20, 0x25, 0x26, 0x2f, 0x2c, 0x29, 0x2a,
+    0x0b, 0x08, 0x0d, 0x0e, 0x07, 0x04, 0x01, 0x02, 0x13, 0x10, 0x15, 0x16, 0x1f, 0x1c, 0x19, 0x1a,
+];
+
 const GALOIS_MUL_9: [u8; 256] = [
     0x00, 0x09, 0x12, 0x1b, 0x24, 0x2d, 0x36, 0x3f, 0x48, 0x41, 0x5a, 0x53, 0x6c, 0x65, 0x7e, 0x77,
     0x90, 0x99, 0x82, 0x8b, 0xb4, 0xbd, 0xa6, 0xaf, 0xd8, 0xd1, 0xca, 0xc3, 0xfc, 0xf5, 0xee, 0xe7,
@@ -132,6 +182,25 @@ const GALOIS_MUL_14: [u8; 256] = [
     0xd7, 0xd9, 0xcb, 0xc5, 0xef, 0xe1, 0xf3, 0xfd, 0xa7, 0xa9, 0xbb, 0xb5, 0x9f, 0x91, 0x83, 0x8d,
 ];
 
+const S_BOX: [u8; 256] = [
+    0x63, 0x7C, 0x77, 0x7B, 0xF2, 0x6B, 0x6F, 0xC5, 0x30, 0x01, 0x67, 0x2B, 0xFE, 0xD7, 0xAB, 0x76,
+    0xCA, 0x82, 0xC9, 0x7D, 0xFA, 0x59, 0x47, 0xF0, 0xAD, 0xD4, 0xA2, 0xAF, 0x9C, 0xA4, 0x72, 0xC0,
+    0xB7, 0xFD, 0x93, 0x26, 0x36, 0x3F, 0xF7, 0xCC, 0x34, 0xA5, 0xE5, 0xF1, 0x71, 0xD8, 0x31, 0x15,
+    0x04, 0xC7, 0x23, 0xC3, 0x18, 0x96, 0x05, 0x9A, 0x07, 0x12, 0x80, 0xE2, 0xEB, 0x27, 0xB2, 0x75,
+    0x09, 0x83, 0x2C, 0x1A, 0x1B, 0x6E, 0x5A, 0xA0, 0x52, 0x3B, 0xD6, 0xB3, 0x29, 0xE3, 0x2F, 0x84,
+    0x53, 0xD1, 0x00, 0xED, 0x20, 0xFC, 0xB1, 0x5B, 0x6A, 0xCB, 0xBE, 0x39, 0x4A, 0x4C, 0x58, 0xCF,
+    0xD0, 0xEF, 0xAA, 0xFB, 0x43, 0x4D, 0x33, 0x85, 0x45, 0xF9, 0x02, 0x7F, 0x50, 0x3C, 0x9F, 0xA8,
+    0x51, 0xA3, 0x40, 0x8F, 0x92, 0x9D, 0x38, 0xF5, 0xBC, 0xB6, 0xDA, 0x21, 0x10, 0xFF, 0xF3, 0xD2,
+    0xCD, 0x0C, 0x13, 0xEC, 0x5F, 0x97, 0x44, 0x17, 0xC4, 0xA7, 0x7E, 0x3D, 0x64, 0x5D, 0x19, 0x73,
+    0x60, 0x81, 0x4F, 0xDC, 0x22, 0x2A, 0x90, 0x88, 0x46, 0xEE, 0xB8, 0x14, 0xDE, 0x5E, 0x0B, 0xDB,
+    0xE0, 0x32, 0x3A, 0x0A, 0x49, 0x06, 0x24, 0x5C, 0xC2, 0xD3, 0xAC, 0x62, 0x91, 0x95, 0xE4, 0x79,
+    0xE7, 0xC8, 0x37, 0x6D, 0x8D, 0xD5, 0x4E, 0xA9, 0x6C, 0x56, 0xF4, 0xEA, 0x65, 0x7A, 0xAE, 0x08,
+    0xBA, 0x78, 0x25, 0x2E, 0x1C, 0xA6, 0xB4, 0xC6, 0xE8, 0xDD, 0x74, 0x1F, 0x4B, 0xBD, 0x8B, 0x8A,
+    0x70, 0x3E, 0xB5, 0x66, 0x48, 0x03, 0xF6, 0x0E, 0x61, 0x35, 0x57, 0xB9, 0x86, 0xC1, 0x1D, 0x9E,
+    0xE1, 0xF8, 0x98, 0x11, 0x69, 0xD9, 0x8E, 0x94, 0x9B, 0x1E, 0x87, 0xE9, 0xCE, 0x55, 0x28, 0xDF,
+    0x8C, 0xA1, 0x89, 0x0D, 0xBF, 0xE6, 0x42, 0x68, 0x41, 0x99, 0x2D, 0x0F, 0xB0, 0x54, 0xBB, 0x16,
+];
+
 const INV_S_BOX: [u8; 256] = [
     0x52, 0x09, 0x6A, 0xD5, 0x30, 0x36, 0xA5, 0x38, 0xBF, 0x40, 0xA3, 0x9E, 0x81, 0xF3, 0xD7, 0xFB,
     0x7C, 0xE3, 0x39, 0x82, 0x9B, 0x2F, 0xFF, 0x87, 0x34, 0x8E, 0x43, 0x44, 0xC4, 0xDE, 0xE9, 0xCB,
@@ -262,6 +331,15 @@ impl ByteStream {
         Ok(bs)
     }
 
+    /// Reads `len` bytes from the operating system's random source.
+    pub fn random(len: usize) -> ByteStream {
+        let mut data = vec![0; len];
+        File::open("/dev/urandom")
+            .and_then(|mut f| f.read_exact(&mut data))
+            .expect("could not read from /dev/urandom");
+        ByteStream { data }
+    }
+
     pub fn into_bytes(self) -> Vec<u8> {
         self.data
     }
@@ -284,7 +362,8 @@ impl ByteStream {
                 offset = (offset + 6) % 8;
             }
             let left_octet = (b << (offset - 2)) & 63u8;
-            let right_octet = self.data
+            let right_octet = self
+                .data
                 .get(i + 1)
                 .unwrap_or(&0u8)
                 .checked_shr(10 - offset)
@@ -334,6 +413,19 @@ impl ByteStream {
         self.len() == 0
     }
 
+    /// Compares two streams in time that depends only on their lengths, never
+    /// on their contents. Use this rather than `==` for MACs and padding.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        if self.data.len() != other.data.len() {
+            return false;
+        }
+        let mut diff = 0u8;
+        for (a, b) in self.data.iter().zip(other.data.iter()) {
+            diff |= a ^ b;
+        }
+        hint::black_box(diff) == 0
+    }
+
     pub fn byte_xor(&mut self, byte: u8) {
         for b in self.data.iter_mut() {
             *b = *b ^ byte
@@ -467,42 +559,58 @@ impl ByteStream {
 
     pub fn key_schedule_core(&mut self, i: i32) {
         self.data.rotate_left(1);
+        self.sub_bytes();
         self.data[0] = self.data[0] ^ RCON[i as usize];
     }
 
     pub fn get_aes_round_keys(&self, key: Self) -> Vec<ByteStream> {
         assert!(key.data.len() == 16);
-        let mut keys = Vec::new();
-        let mut last_key = key;
-        for i in 1..12 {
-            let mut t = ByteStream::from_bytes(&[
-                last_key.data[12],
-                last_key.data[13],
-                last_key.data[14],
-                last_key.data[15],
-            ]);
+        let mut keys = vec![key];
+        for i in 1..11 {
+            let last_key = &keys[keys.len() - 1];
+            let mut t = ByteStream::from_bytes(&last_key.data[12..16]);
             t.key_schedule_core(i);
             let mut new_key = ByteStream::new();
 
-            for chunk in last_key.iter_blocks_mut(4) {
-                let mut new_chunk =
-                    ByteStream::from_bytes(&[chunk[0], chunk[1], chunk[2], chunk[3]]);
+            // Each word is the xor of the word one round key earlier and the
+            // word just before it, which for the first word is run through
+            // the schedule core.
+            for chunk in last_key.iter_blocks(4) {
+                let mut new_chunk = ByteStream::from_bytes(chunk);
                 new_chunk.repeating_xor(&t);
+                t = new_chunk.clone();
                 new_key.append(&mut new_chunk);
             }
 
             assert_eq!(new_key.data.len(), 16);
 
-            keys.push(last_key);
-            last_key = new_key;
+            keys.push(new_key);
         }
-        keys.push(last_key);
-        keys.remove(0);
         keys
     }
 
     const ECB_BLOCK_SIZE: usize = 16;
 
+    fn shift_rows(&mut self) {
+        for chunk in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE) {
+            if chunk.len() < Self::ECB_BLOCK_SIZE {
+                panic!("unpadded blocks!");
+            }
+            let tmp = chunk[1];
+            chunk[1] = chunk[5];
+            chunk[5] = chunk[9];
+            chunk[9] = chunk[13];
+            chunk[13] = tmp;
+            chunk.swap(2, 10);
+            chunk.swap(6, 14);
+            let tmp = chunk[3];
+            chunk[3] = chunk[15];
+            chunk[15] = chunk[11];
+            chunk[11] = chunk[7];
+            chunk[7] = tmp;
+        }
+    }
+
     fn unshift_rows(&mut self) {
         for chunk in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE) {
             if chunk.len() < Self::ECB_BLOCK_SIZE {
@@ -527,41 +635,89 @@ impl ByteStream {
         }
     }
 
+    fn sub_bytes(&mut self) {
+        for b in self.data.iter_mut() {
+            *b = S_BOX[*b as usize];
+        }
+    }
+
     fn unsub_bytes(&mut self) {
         for b in self.data.iter_mut() {
             *b = INV_S_BOX[*b as usize];
         }
     }
 
+    fn mix_columns(&mut self) {
+        for chunk in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE) {
+            if chunk.len() < Self::ECB_BLOCK_SIZE {
+                panic!("unpadded blocks!");
+            }
+            for col in chunk.chunks_mut(4) {
+                let c = [
+                    col[0] as usize,
+                    col[1] as usize,
+                    col[2] as usize,
+                    col[3] as usize,
+                ];
+                col[0] = GALOIS_MUL_2[c[0]] ^ GALOIS_MUL_3[c[1]] ^ c[2] as u8 ^ c[3] as u8;
+                col[1] = c[0] as u8 ^ GALOIS_MUL_2[c[1]] ^ GALOIS_MUL_3[c[2]] ^ c[3] as u8;
+                col[2] = c[0] as u8 ^ c[1] as u8 ^ GALOIS_MUL_2[c[2]] ^ GALOIS_MUL_3[c[3]];
+                col[3] = GALOIS_MUL_3[c[0]] ^ c[1] as u8 ^ c[2] as u8 ^ GALOIS_MUL_2[c[3]];
+            }
+        }
+    }
+
     fn unmix_columns(&mut self) {
         for chunk in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE) {
             if chunk.len() < Self::ECB_BLOCK_SIZE {
                 panic!("unpadded blocks!");
             }
-            for col in 0..4 {
-                chunk[0 + 4 * col] = GALOIS_MUL_14[chunk[0 + 4 * col] as usize]
-                    ^ GALOIS_MUL_11[chunk[1 + 4 * col] as usize]
-                    ^ GALOIS_MUL_13[chunk[2 + 4 * col] as usize]
-                    ^ GALOIS_MUL_9[chunk[3 + 4 * col] as usize];
-                chunk[1 + 4 * col] = GALOIS_MUL_9[chunk[0 + 4 * col] as usize]
-                    ^ GALOIS_MUL_14[chunk[1 + 4 * col] as usize]
-                    ^ GALOIS_MUL_11[chunk[2 + 4 * col] as usize]
-                    ^ GALOIS_MUL_13[chunk[3 + 4 * col] as usize];
-                chunk[2 + 4 * col] = GALOIS_MUL_13[chunk[0 + 4 * col] as usize]
-                    ^ GALOIS_MUL_9[chunk[1 + 4 * col] as usize]
-                    ^ GALOIS_MUL_14[chunk[2 + 4 * col] as usize]
-                    ^ GALOIS_MUL_11[chunk[3 + 4 * col] as usize];
-                chunk[3 + 4 * col] = GALOIS_MUL_11[chunk[0 + 4 * col] as usize]
-                    ^ GALOIS_MUL_13[chunk[1 + 4 * col] as usize]
-                    ^ GALOIS_MUL_9[chunk[2 + 4 * col] as usize]
-                    ^ GALOIS_MUL_14[chunk[3 + 4 * col] as usize];
+            for col in chunk.chunks_mut(4) {
+                let c = [
+                    col[0] as usize,
+                    col[1] as usize,
+                    col[2] as usize,
+                    col[3] as usize,
+                ];
+                col[0] = GALOIS_MUL_14[c[0]]
+                    ^ GALOIS_MUL_11[c[1]]
+                    ^ GALOIS_MUL_13[c[2]]
+                    ^ GALOIS_MUL_9[c[3]];
+                col[1] = GALOIS_MUL_9[c[0]]
+                    ^ GALOIS_MUL_14[c[1]]
+                    ^ GALOIS_MUL_11[c[2]]
+                    ^ GALOIS_MUL_13[c[3]];
+                col[2] = GALOIS_MUL_13[c[0]]
+                    ^ GALOIS_MUL_9[c[1]]
+                    ^ GALOIS_MUL_14[c[2]]
+                    ^ GALOIS_MUL_11[c[3]];
+                col[3] = GALOIS_MUL_11[c[0]]
+                    ^ GALOIS_MUL_13[c[1]]
+                    ^ GALOIS_MUL_9[c[2]]
+                    ^ GALOIS_MUL_14[c[3]];
             }
         }
     }
 
-    pub fn decrypt_aes_128_ecb(&mut self, key: Self) {
-        let round_keys = self.get_aes_round_keys(key);
+    fn encrypt_aes_128_with(&mut self, round_keys: &[ByteStream]) {
+        // round 0
+        self.repeating_xor(&round_keys[0]);
+
+        // rounds 1-9
+        for round_key in round_keys.iter().take(10).skip(1) {
+            self.sub_bytes();
+            self.shift_rows();
+            self.mix_columns();
+            self.repeating_xor(round_key);
+        }
+
+        // round 10
+        self.sub_bytes();
+        self.shift_rows();
+        self.repeating_xor(&round_keys[10]);
+    }
 
+    fn decrypt_aes_128_with(&mut self, round_keys: &[ByteStream]) {
         // round 10
         self.repeating_xor(&round_keys[10]);
         self.unshift_rows();
@@ -578,6 +734,96 @@ impl ByteStream {
         // round 0
         self.repeating_xor(&round_keys[0]);
     }
+
+    pub fn encrypt_aes_128_ecb(&mut self, key: Self) {
+        let round_keys = self.get_aes_round_keys(key);
+        self.encrypt_aes_128_with(&round_keys);
+    }
+
+    pub fn decrypt_aes_128_ecb(&mut self, key: Self) {
+        let round_keys = self.get_aes_round_keys(key);
+        self.decrypt_aes_128_with(&round_keys);
+    }
+
+    /// Encrypts a padded stream in CBC mode.
+    pub fn encrypt_aes_128_cbc(&mut self, key: Self, iv: &Self) {
+        assert!(iv.len() == Self::ECB_BLOCK_SIZE);
+        let round_keys = self.get_aes_round_keys(key);
+        let mut prev = iv.clone();
+        for chunk in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE) {
+            let mut block = ByteStream::from_bytes(chunk);
+            block.repeating_xor(&prev);
+            block.encrypt_aes_128_with(&round_keys);
+            chunk.copy_from_slice(&block.data);
+            prev = block;
+        }
+    }
+
+    /// Decrypts a CBC mode stream, leaving any padding in place.
+    pub fn decrypt_aes_128_cbc(&mut self, key: Self, iv: &Self) {
+        assert!(iv.len() == Self::ECB_BLOCK_SIZE);
+        let round_keys = self.get_aes_round_keys(key);
+        let mut prev = iv.clone();
+        for chunk in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE) {
+            let ciphertext = ByteStream::from_bytes(chunk);
+            let mut block = ciphertext.clone();
+            block.decrypt_aes_128_with(&round_keys);
+            block.repeating_xor(&prev);
+            chunk.copy_from_slice(&block.data);
+            prev = ciphertext;
+        }
+    }
+
+    /// Encrypts or decrypts a stream in CTR mode, with a little-endian 64-bit
+    /// nonce followed by a little-endian 64-bit block counter.
+    pub fn apply_aes_128_ctr(&mut self, key: Self, nonce: u64) {
+        let round_keys = self.get_aes_round_keys(key);
+        for (counter, chunk) in self.iter_blocks_mut(Self::ECB_BLOCK_SIZE).enumerate() {
+            let mut keystream = ByteStream::from_bytes(&nonce.to_le_bytes());
+            keystream.append(&mut ByteStream::from_bytes(&(counter as u64).to_le_bytes()));
+            keystream.encrypt_aes_128_with(&round_keys);
+            for (byte, k) in chunk.iter_mut().zip(keystream.iter()) {
+                *byte ^= k;
+            }
+        }
+    }
+
+    pub fn pkcs7_pad(&mut self, block_size: usize) {
+        assert!(block_size > 0 && block_size < 256);
+        let pad_len = block_size - self.data.len() % block_size;
+        for _ in 0..pad_len {
+            self.push(pad_len as u8);
+        }
+    }
+
+    /// Strips PKCS#7 padding, leaving the stream untouched if it is invalid.
+    ///
+    /// The last 255 bytes are always all inspected and the differences
+    /// accumulated, so that the time taken depends only on the length of the
+    /// stream and not on where or whether the padding is wrong.
+    pub fn pkcs7_unpad(&mut self) -> Result<(), PaddingError> {
+        let len = self.data.len() as u64;
+        let pad_len = match self.data.last() {
+            Some(&n) => u64::from(n),
+            None => return Err(PaddingError {}),
+        };
+        // All ones if `a < b` and zero otherwise, for operands below 2^63.
+        let less = |a: u64, b: u64| 0u64.wrapping_sub(a.wrapping_sub(b) >> 63);
+
+        // Nonzero if the padding is empty, longer than the stream, or has a
+        // byte other than `pad_len` anywhere in it.
+        let mut bad = !less(0, pad_len) | less(len, pad_len);
+        for (i, b) in self.data.iter().rev().take(255).enumerate() {
+            bad |= less(i as u64, pad_len) & (u64::from(*b) ^ pad_len);
+        }
+        let valid = 0u64.wrapping_sub(1 ^ ((bad | bad.wrapping_neg()) >> 63));
+        self.data.truncate((len - (pad_len & valid)) as usize);
+        if hint::black_box(valid) == 0 {
+            Err(PaddingError {})
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl IntoByteStream for Vec<u8> {
@@ -691,10 +937,83 @@ mod tests {
         fn it_converts_b64_to_hex() {
             let bs = ByteStream::from_b64(
                 "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t",
-            ).unwrap();
+            )
+            .unwrap();
             assert_eq!(bs.into_hex(), "49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d");
         }
 
+        #[test]
+        fn it_encrypts_and_decrypts_aes_128() {
+            let key = ByteStream::from_hex("000102030405060708090a0b0c0d0e0f").unwrap();
+            let mut bs = ByteStream::from_hex("00112233445566778899aabbccddeeff").unwrap();
+            bs.encrypt_aes_128_ecb(key.clone());
+            assert_eq!(bs.clone().into_hex(), "69c4e0d86a7b0430d8cdb78070b4c55a");
+            bs.decrypt_aes_128_ecb(key);
+            assert_eq!(bs.into_hex(), "00112233445566778899aabbccddeeff");
+        }
+
+        #[test]
+        fn it_round_trips_aes_128_cbc() {
+            let key = ByteStream::from_ascii("YELLOW SUBMARINE").unwrap();
+            let iv = ByteStream::from(vec![7; 16]);
+            let plaintext = ByteStream::from_ascii("I'm back and I'm ringin' the bell").unwrap();
+            let mut bs = plaintext.clone();
+            bs.pkcs7_pad(16);
+            assert_eq!(bs.len(), 48);
+            bs.encrypt_aes_128_cbc(key.clone(), &iv);
+            bs.decrypt_aes_128_cbc(key, &iv);
+            bs.pkcs7_unpad().unwrap();
+            assert_eq!(bs, plaintext);
+        }
+
+        #[test]
+        fn it_decrypts_aes_128_ctr() {
+            let key = ByteStream::from_ascii("YELLOW SUBMARINE").unwrap();
+            let mut bs = ByteStream::from_hex(
+                "2fbee76bf9eb16c2afca777a1f33a81bb1874cb5ec4d5bbdaaf63fdacc8b5f384fc1ecb23132542eeffafe45d7d0a4afa0e2d215",
+            )
+            .unwrap();
+            bs.apply_aes_128_ctr(key, 0);
+            assert_eq!(
+                bs,
+                ByteStream::from_ascii("Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ")
+                    .unwrap()
+            );
+        }
+
+        #[test]
+        fn it_rejects_bad_padding() {
+            let mut bs = ByteStream::from_ascii("ICE ICE BABY\x04\x04\x04\x04").unwrap();
+            assert!(bs.pkcs7_unpad().is_ok());
+            assert_eq!(bs, ByteStream::from_ascii("ICE ICE BABY").unwrap());
+            let mut bs = ByteStream::from_ascii("ICE ICE BABY\x01\x02\x03\x04").unwrap();
+            assert!(bs.pkcs7_unpad().is_err());
+            let mut bs = ByteStream::from_ascii("ICE ICE BABY\x05\x05\x05\x05").unwrap();
+            assert!(bs.pkcs7_unpad().is_err());
+        }
+
+        #[test]
+        fn it_rejects_malformed_padding_without_changing_the_stream() {
+            for bytes in vec![
+                Vec::new(),
+                vec![0],
+                vec![b'A', 0],
+                vec![3, 3],
+                vec![2, 1, 2],
+                vec![255; 254],
+            ] {
+                let mut bs = ByteStream::from(bytes.clone());
+                assert!(bs.pkcs7_unpad().is_err());
+                assert_eq!(bs.into_bytes(), bytes);
+            }
+            let mut bs = ByteStream::from(vec![16; 16]);
+            assert!(bs.pkcs7_unpad().is_ok());
+            assert!(bs.is_empty());
+            let mut bs = ByteStream::from(vec![255; 256]);
+            assert!(bs.pkcs7_unpad().is_ok());
+            assert_eq!(bs.len(), 1);
+        }
+
         #[test]
         fn it_performs_xor() {
             let mut bs1 = ByteStream::from_hex("1c0111001f010100061a024b53535009181c").unwrap();
@@ -704,6 +1023,130 @@ mod tests {
         }
     }
 
+    mod ct_eq {
+        use super::super::ByteStream;
+        use std::fs;
+        use std::path::Path;
+        use std::time::{Duration, Instant};
+
+        /// Words that mark an identifier as holding a secret, wherever they
+        /// appear in it: `pad_len` and `expected_mac` count as well as `mac`.
+        const SECRET_WORDS: [&str; 7] = [
+            "mac",
+            "hmac",
+            "tag",
+            "signature",
+            "digest",
+            "pad",
+            "padding",
+        ];
+
+        fn is_secret(ident: &str) -> bool {
+            ident
+                .split('_')
+                .any(|word| SECRET_WORDS.contains(&word.to_lowercase().as_str()))
+        }
+
+        #[test]
+        fn it_compares_for_equality() {
+            let a = ByteStream::from_ascii("YELLOW SUBMARINE").unwrap();
+            let b = ByteStream::from_ascii("YELLOW SUBMARINF").unwrap();
+            assert!(a.ct_eq(&a.clone()));
+            assert!(!a.ct_eq(&b));
+            assert!(!a.ct_eq(&ByteStream::from_ascii("YELLOW").unwrap()));
+            assert!(ByteStream::new().ct_eq(&ByteStream::new()));
+        }
+
+        fn median_time(a: &ByteStream, b: &ByteStream) -> Duration {
+            let mut samples: Vec<Duration> = (0..201)
+                .map(|_| {
+                    let start = Instant::now();
+                    assert!(!a.ct_eq(b));
+                    start.elapsed()
+                })
+                .collect();
+            samples.sort();
+            samples[samples.len() / 2]
+        }
+
+        #[test]
+        fn it_takes_the_same_time_wherever_the_mismatch_is() {
+            let a = ByteStream::from(vec![0x42; 1 << 16]);
+            let mismatch_at = |i: usize| {
+                let mut bytes = a.clone().into_bytes();
+                bytes[i] ^= 1;
+                ByteStream::from(bytes)
+            };
+            let first = mismatch_at(0);
+            let last = mismatch_at(a.len() - 1);
+
+            // Interleave the measurements so that any drift in machine load
+            // affects both positions equally.
+            let mut ratios = Vec::new();
+            for _ in 0..5 {
+                let early = median_time(&a, &first).as_nanos() as f64;
+                let late = median_time(&a, &last).as_nanos() as f64;
+                ratios.push(early / late);
+            }
+            ratios.sort_by(|x, y| x.partial_cmp(y).unwrap());
+            let ratio = ratios[ratios.len() / 2];
+            assert!(ratio > 0.5 && ratio < 2.0, "timing ratio {}", ratio);
+        }
+
+        /// Returns the identifiers directly on either side of each `==` or
+        /// `!=` in `line`, and every identifier in it if it short-circuits
+        /// through `.any` or `.all`.
+        fn compared_identifiers(line: &str) -> Vec<String> {
+            let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+            let mut idents = Vec::new();
+            if line.contains(".any(") || line.contains(".all(") {
+                idents.extend(line.split(|c| !is_ident(c)).map(String::from));
+            }
+            for (i, _) in line.match_indices("==").chain(line.match_indices("!=")) {
+                let left = line[..i].trim_end().trim_end_matches([')', '(']);
+                let left_start = left.rfind(|c| !is_ident(c)).map_or(0, |j| j + 1);
+                idents.push(left[left_start..].to_string());
+
+                let right = line[i + 2..].trim_start().trim_start_matches(['*', '&']);
+                let right_end = right.find(|c| !is_ident(c)).unwrap_or(right.len());
+                idents.push(right[..right_end].to_string());
+            }
+            idents
+        }
+
+        fn audit_dir(dir: &Path, violations: &mut Vec<String>) {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    audit_dir(&path, violations);
+                    continue;
+                }
+                let source = fs::read_to_string(&path).unwrap();
+                for (n, line) in source.lines().enumerate() {
+                    let code = line.split("//").next().unwrap();
+                    if line.contains("ct-audit: allow") {
+                        continue;
+                    }
+                    if compared_identifiers(code).iter().any(|i| is_secret(i)) {
+                        violations.push(format!("{}:{}: {}", path.display(), n + 1, line.trim()));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn it_is_used_for_every_secret_comparison() {
+            let mut violations = Vec::new();
+            let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+            audit_dir(&src, &mut violations);
+            assert!(
+                violations.is_empty(),
+                "variable-time comparisons of secrets:\n{}",
+                violations.join("\n")
+            );
+        }
+    }
+
     mod edit_dist {
         use super::super::edit_dist;
 
@@ -0,0 +1,244 @@
+//!
+//! CBC-MAC over AES-128, and the forgeries it allows when the attacker
+//! controls the IV, when MACs of several messages can be chained, and when
+//! it is used as a hash.
+//!
+
+use byte_stream::ByteStream;
+
+const BLOCK_SIZE: usize = 16;
+
+/// The key that `hash` uses, which is public by design.
+const HASH_KEY: &str = "YELLOW SUBMARINE";
+
+/// The last block of the AES-128-CBC encryption of the PKCS#7-padded
+/// message.
+pub fn cbc_mac(key: &ByteStream, iv: &ByteStream, message: &ByteStream) -> ByteStream {
+    let mut padded = message.clone();
+    padded.pkcs7_pad(BLOCK_SIZE);
+    last_block(key, iv, padded)
+}
+
+/// Chains `blocks`, which must already be block aligned, and returns the
+/// final state.
+fn last_block(key: &ByteStream, iv: &ByteStream, mut blocks: ByteStream) -> ByteStream {
+    blocks.encrypt_aes_128_cbc(key.clone(), iv);
+    let bytes = blocks.into_bytes();
+    ByteStream::from_bytes(&bytes[bytes.len() - BLOCK_SIZE..])
+}
+
+pub fn verify(key: &ByteStream, iv: &ByteStream, message: &ByteStream, mac: &ByteStream) -> bool {
+    cbc_mac(key, iv, message).ct_eq(mac)
+}
+
+fn xor(a: &[u8], b: &[u8]) -> ByteStream {
+    ByteStream::from(
+        a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| x ^ y)
+            .collect::<Vec<u8>>(),
+    )
+}
+
+/// Replaces the first block of a message whose IV is sent alongside it, and
+/// returns the new message and the IV that keeps the MAC unchanged. Returns
+/// `None` unless `replacement` and `iv` are exactly one block and the message
+/// has at least one.
+pub fn forge_first_block(
+    message: &ByteStream,
+    iv: &ByteStream,
+    replacement: &ByteStream,
+) -> Option<(ByteStream, ByteStream)> {
+    if replacement.len() != BLOCK_SIZE || iv.len() != BLOCK_SIZE || message.len() < BLOCK_SIZE {
+        return None;
+    }
+    let bytes = message.clone().into_bytes();
+    let replacement = replacement.clone().into_bytes();
+    let original = &bytes[..BLOCK_SIZE];
+    let iv = iv.clone().into_bytes();
+    let forged_iv = xor(&xor(&iv, original).into_bytes(), &replacement);
+
+    let mut forged = ByteStream::from(replacement);
+    forged.append(&mut ByteStream::from_bytes(&bytes[BLOCK_SIZE..]));
+    Some((forged, forged_iv))
+}
+
+/// Given a message and its MAC, and an `extension` whose MAC under the same
+/// key and a zero IV is known, builds a message with that second MAC:
+/// `pad(message) || (extension[0] ^ mac) || extension[1..]`.
+///
+/// The forged message ends the way `extension` does, so it gets the same
+/// padding. Returns `None` if `extension` is shorter than a block, since its
+/// padding would then share the block that `mac` has to be folded into; the
+/// MAC of `pad(extension)` can be used instead.
+pub fn extend(
+    message: &ByteStream,
+    mac: &ByteStream,
+    extension: &ByteStream,
+) -> Option<ByteStream> {
+    if extension.len() < BLOCK_SIZE {
+        return None;
+    }
+    let mut forged = message.clone();
+    forged.pkcs7_pad(BLOCK_SIZE);
+    let extension = extension.clone().into_bytes();
+    let mac = mac.clone().into_bytes();
+    forged.append(&mut xor(&extension[..BLOCK_SIZE], &mac));
+    forged.append(&mut ByteStream::from_bytes(&extension[BLOCK_SIZE..]));
+    Some(forged)
+}
+
+/// CBC-MAC used as a hash, with a public key and a zero IV.
+pub fn hash(message: &ByteStream) -> ByteStream {
+    let key = ByteStream::from_ascii(HASH_KEY).unwrap();
+    cbc_mac(&key, &ByteStream::from(vec![0; BLOCK_SIZE]), message)
+}
+
+/// Builds a JavaScript snippet that runs `replacement` and hashes to the same
+/// value as `original`.
+///
+/// The replacement is followed by a line comment padded to a block boundary,
+/// then a block chosen to bring the chaining state back to where `original`
+/// starts, then `original` minus its first block. The chosen block lands
+/// inside the comment, so the filler is varied until it has no line breaks.
+/// Returns `None` if `original` is shorter than a block, as its padding would
+/// then have to come out of the chosen block.
+pub fn forge_javascript(original: &ByteStream, replacement: &str) -> Option<ByteStream> {
+    if original.len() < BLOCK_SIZE {
+        return None;
+    }
+    let key = ByteStream::from_ascii(HASH_KEY).unwrap();
+    let zero_iv = ByteStream::from(vec![0; BLOCK_SIZE]);
+    let original = original.clone().into_bytes();
+
+    let mut prefix = ByteStream::from_bytes(replacement.as_bytes());
+    prefix.append(&mut ByteStream::from_ascii("//").unwrap());
+    while !prefix.len().is_multiple_of(BLOCK_SIZE) {
+        prefix.push(b' ');
+    }
+    loop {
+        let state = last_block(&key, &zero_iv, prefix.clone()).into_bytes();
+        let mut glue = xor(&state, &original[..BLOCK_SIZE]);
+        if glue.iter().all(|b| *b != b'\n' && *b != b'\r') {
+            let mut forged = prefix;
+            forged.append(&mut glue);
+            forged.append(&mut ByteStream::from_bytes(&original[BLOCK_SIZE..]));
+            return Some(forged);
+        }
+        // Change the chaining state with another block of comment.
+        for _ in 0..BLOCK_SIZE {
+            prefix.push(b'a' + ByteStream::random(1).into_bytes()[0] % 26);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ascii(s: &str) -> ByteStream {
+        ByteStream::from_ascii(s).unwrap()
+    }
+
+    #[test]
+    fn it_matches_a_known_hash() {
+        assert_eq!(
+            hash(&ascii("alert('MZA who was that?');\n")).into_hex(),
+            "296b8d7cb78a243dda4d0a61d33bbdd1"
+        );
+    }
+
+    #[test]
+    fn it_forges_a_message_with_a_chosen_iv() {
+        let key = ByteStream::random(BLOCK_SIZE);
+        let iv = ByteStream::random(BLOCK_SIZE);
+        let message = ascii("from=1337&to=1337&amount=1000000");
+        let mac = cbc_mac(&key, &iv, &message);
+
+        let (forged, forged_iv) =
+            forge_first_block(&message, &iv, &ascii("from=0042&to=133")).unwrap();
+        assert_eq!(forged, ascii("from=0042&to=1337&amount=1000000"));
+        assert!(verify(&key, &forged_iv, &forged, &mac));
+        assert!(!verify(&key, &iv, &forged, &mac));
+
+        let replacement = ascii("from=0042&to=133");
+        assert_eq!(
+            forge_first_block(&message, &ascii("short"), &replacement),
+            None
+        );
+        assert_eq!(forge_first_block(&message, &iv, &ascii("from=0042")), None);
+    }
+
+    #[test]
+    fn it_extends_a_message_under_a_fixed_iv() {
+        let key = ByteStream::random(BLOCK_SIZE);
+        let zero_iv = ByteStream::from(vec![0; BLOCK_SIZE]);
+        let victim = ascii("from=0042&tx_list=0007:5000;0099:25");
+        let victim_mac = cbc_mac(&key, &zero_iv, &victim);
+        for own in &[
+            "from=1337&tx_list=1337:1000000",
+            "from=1337&tx_list",
+            "1337:1000000;;;;",
+        ] {
+            let own = ascii(own);
+            let own_mac = cbc_mac(&key, &zero_iv, &own);
+            let forged = extend(&victim, &victim_mac, &own).unwrap();
+            assert!(verify(&key, &zero_iv, &forged, &own_mac));
+            assert!(forged
+                .into_bytes()
+                .ends_with(&own.into_bytes()[BLOCK_SIZE..]));
+        }
+    }
+
+    #[test]
+    fn it_extends_with_a_short_extension_only_once_padded() {
+        let key = ByteStream::random(BLOCK_SIZE);
+        let zero_iv = ByteStream::from(vec![0; BLOCK_SIZE]);
+        let victim = ascii("from=0042&tx_list=0007:5000");
+        let victim_mac = cbc_mac(&key, &zero_iv, &victim);
+        let short = ascii("1337:1000000");
+        assert_eq!(extend(&victim, &victim_mac, &short), None);
+
+        let mut padded = short.clone();
+        padded.pkcs7_pad(BLOCK_SIZE);
+        let padded_mac = cbc_mac(&key, &zero_iv, &padded);
+        let forged = extend(&victim, &victim_mac, &padded).unwrap();
+        assert!(verify(&key, &zero_iv, &forged, &padded_mac));
+    }
+
+    fn collides(original: &str) {
+        let original = ascii(original);
+        let forged = forge_javascript(&original, "alert('Ayo, the Wu is back!');").unwrap();
+
+        assert_eq!(hash(&forged), hash(&original));
+        let bytes = forged.into_bytes();
+        assert!(bytes.starts_with(b"alert('Ayo, the Wu is back!');//"));
+        let comment_end = bytes.len() - (original.len() - BLOCK_SIZE);
+        assert!(bytes[..comment_end]
+            .iter()
+            .all(|b| *b != b'\n' && *b != b'\r'));
+    }
+
+    #[test]
+    fn it_forges_colliding_javascript() {
+        collides("alert('MZA who was that?');\n");
+        collides("alert('MZA');\n\n\n");
+        collides("alert('MZA who was that?'); alert('Ghostface');\n");
+    }
+
+    #[test]
+    fn it_forges_non_ascii_javascript() {
+        let original = ascii("alert('MZA who was that?');\n");
+        let forged = forge_javascript(&original, "alert('Łódź, café');").unwrap();
+        assert_eq!(hash(&forged), hash(&original));
+        assert!(forged
+            .into_bytes()
+            .starts_with("alert('Łódź, café');//".as_bytes()));
+    }
+
+    #[test]
+    fn it_refuses_to_forge_snippets_shorter_than_a_block() {
+        assert_eq!(forge_javascript(&ascii("alert(1);\n"), "alert(2);"), None);
+        assert_eq!(forge_javascript(&ByteStream::new(), "alert(2);"), None);
+    }
+}
@@ -0,0 +1,156 @@
+//!
+//! Recovering a session cookie from the length of compressed, then encrypted,
+//! requests that also carry attacker-chosen bodies (CRIME).
+//!
+
+use byte_stream::ByteStream;
+use deflate::zlib_compress;
+
+const BLOCK_SIZE: usize = 16;
+
+/// What the attacker knows precedes the secret in every request.
+const COOKIE_PREFIX: &str = "sessionid=";
+
+/// The characters a session id is drawn from, plus the `\r` that ends it.
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=\r";
+
+/// Distinct bytes that occur nowhere in a request, so that prepending them to
+/// a guess shifts it without giving the compressor anything to match. The
+/// narrow ones cost eight bits each under the fixed Huffman code and the wide
+/// ones nine, so between them they reach every bit offset within a block.
+const NARROW_FILLER: &[u8] = b"!@#$%^&*()~_[]{}";
+const WIDE_FILLER: &[u8] = &[0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Ctr,
+    Cbc,
+}
+
+pub struct Oracle {
+    mode: Mode,
+    session_id: String,
+}
+
+impl Oracle {
+    pub fn new(mode: Mode, session_id: &str) -> Oracle {
+        Oracle {
+            mode,
+            session_id: String::from(session_id),
+        }
+    }
+
+    fn request(&self, body: &ByteStream) -> ByteStream {
+        let mut request = ByteStream::from_ascii(&format!(
+            "POST / HTTP/1.1\r\nHost: hapless.com\r\nCookie: {}{}\r\nContent-Length: {}\r\n",
+            COOKIE_PREFIX,
+            self.session_id,
+            body.len()
+        ))
+        .unwrap();
+        request.append(&mut body.clone());
+        request
+    }
+
+    /// The length of the compressed request carrying `body`, encrypted under
+    /// a fresh key and nonce or IV.
+    pub fn length(&self, body: &ByteStream) -> usize {
+        let mut ciphertext = zlib_compress(&self.request(body));
+        let key = ByteStream::random(BLOCK_SIZE);
+        match self.mode {
+            Mode::Ctr => {
+                let nonce = ByteStream::random(8).into_bytes();
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(&nonce);
+                ciphertext.apply_aes_128_ctr(key, u64::from_le_bytes(bytes));
+            }
+            Mode::Cbc => {
+                ciphertext.pkcs7_pad(BLOCK_SIZE);
+                ciphertext.encrypt_aes_128_cbc(key, &ByteStream::random(BLOCK_SIZE));
+            }
+        }
+        ciphertext.len()
+    }
+}
+
+/// The one byte of `ALPHABET` that extends `known` to the shortest request,
+/// if it is the only one.
+///
+/// A correct guess lets the compressor extend its match against the cookie by
+/// a byte, which saves seven or eight bits of output. That saving only shows
+/// when it moves the request across a byte boundary, or a block boundary under
+/// CBC, and a wrong guess can happen to compress as well, so filler is
+/// prepended until exactly one guess stands out.
+fn next_byte(oracle: &Oracle, known: &[u8]) -> Option<u8> {
+    for wide in 0..=WIDE_FILLER.len() {
+        for narrow in 0..=NARROW_FILLER.len() {
+            let mut prefix = ByteStream::from_bytes(&WIDE_FILLER[..wide]);
+            prefix.append(&mut ByteStream::from_bytes(&NARROW_FILLER[..narrow]));
+            prefix.append(&mut ByteStream::from_ascii(COOKIE_PREFIX).unwrap());
+            prefix.append(&mut ByteStream::from_bytes(known));
+
+            let lengths: Vec<(usize, u8)> = ALPHABET
+                .iter()
+                .map(|&guess| {
+                    let mut body = prefix.clone();
+                    body.push(guess);
+                    (oracle.length(&body), guess)
+                })
+                .collect();
+            let shortest = lengths.iter().map(|(l, _)| *l).min()?;
+            let mut winners = lengths.iter().filter(|(l, _)| *l == shortest);
+            if let (Some(&(_, guess)), None) = (winners.next(), winners.next()) {
+                return Some(guess);
+            }
+        }
+    }
+    None
+}
+
+/// Recovers the session id one byte at a time, stopping at the `\r` that ends
+/// the cookie line. Returns `None` if some byte can't be told apart.
+pub fn recover_session_id(oracle: &Oracle) -> Option<String> {
+    let mut known = Vec::new();
+    loop {
+        match next_byte(oracle, &known)? {
+            b'\r' => return String::from_utf8(known).ok(),
+            byte => known.push(byte),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_ID: &str = "TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=";
+
+    #[test]
+    fn it_leaks_through_the_length() {
+        let oracle = Oracle::new(Mode::Ctr, SESSION_ID);
+        let right = ByteStream::from_ascii("sessionid=TmV2ZXIgcmV2").unwrap();
+        let wrong = ByteStream::from_ascii("sessionid=XkP9qQzjw7Lb").unwrap();
+        assert!(oracle.length(&right) < oracle.length(&wrong));
+    }
+
+    #[test]
+    fn it_pads_with_bytes_absent_from_requests() {
+        let oracle = Oracle::new(Mode::Ctr, SESSION_ID);
+        let request = oracle.request(&ByteStream::new()).into_bytes();
+        for byte in NARROW_FILLER.iter().chain(WIDE_FILLER) {
+            assert!(!request.contains(byte) && !ALPHABET.contains(byte));
+        }
+    }
+
+    #[test]
+    fn it_recovers_the_session_id_under_ctr() {
+        let oracle = Oracle::new(Mode::Ctr, SESSION_ID);
+        assert_eq!(recover_session_id(&oracle), Some(String::from(SESSION_ID)));
+    }
+
+    #[test]
+    fn it_recovers_the_session_id_under_cbc() {
+        let oracle = Oracle::new(Mode::Cbc, SESSION_ID);
+        assert_eq!(recover_session_id(&oracle), Some(String::from(SESSION_ID)));
+    }
+}
@@ -0,0 +1,278 @@
+//!
+//! A DEFLATE (RFC 1951) compressor, with the zlib (RFC 1950) wrapper.
+//!
+//! Matches are found greedily with hash chains over a 32KiB window, and every
+//! stream is a single block coded with the fixed Huffman tables, which is
+//! enough for the output to depend on how well the input compresses.
+//!
+
+use byte_stream::ByteStream;
+use std::collections::HashMap;
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 256;
+const END_OF_BLOCK: u16 = 256;
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Packs bits into bytes least significant bit first, as DEFLATE expects.
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit: u8,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            bit: 0,
+        }
+    }
+
+    /// Writes the low `count` bits of `value`, least significant first.
+    fn write_bits(&mut self, value: u32, count: u8) {
+        for i in 0..count {
+            if self.bit == 0 {
+                self.bytes.push(0);
+            }
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= (((value >> i) & 1) as u8) << self.bit;
+            self.bit = (self.bit + 1) % 8;
+        }
+    }
+
+    /// Writes a Huffman code, which goes most significant bit first.
+    fn write_code(&mut self, code: u16, length: u8) {
+        for i in (0..length).rev() {
+            self.write_bits(u32::from(code >> i) & 1, 1);
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// The fixed Huffman code for a literal/length symbol.
+fn fixed_literal_code(symbol: u16) -> (u16, u8) {
+    match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    }
+}
+
+/// Finds which of `bases` a value falls under.
+fn bucket(bases: &[u16], value: usize) -> usize {
+    bases
+        .iter()
+        .rposition(|b| usize::from(*b) <= value)
+        .unwrap()
+}
+
+fn write_literal(writer: &mut BitWriter, symbol: u16) {
+    let (code, length) = fixed_literal_code(symbol);
+    writer.write_code(code, length);
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let i = bucket(&LENGTH_BASES, length);
+    write_literal(writer, 257 + i as u16);
+    writer.write_bits(
+        (length - usize::from(LENGTH_BASES[i])) as u32,
+        LENGTH_EXTRA_BITS[i],
+    );
+
+    let i = bucket(&DISTANCE_BASES, distance);
+    writer.write_code(i as u16, 5);
+    writer.write_bits(
+        (distance - usize::from(DISTANCE_BASES[i])) as u32,
+        DISTANCE_EXTRA_BITS[i],
+    );
+}
+
+/// The longest earlier match for the data at `pos`, as `(length, distance)`.
+fn longest_match(data: &[u8], pos: usize, candidates: &[usize]) -> Option<(usize, usize)> {
+    let limit = (data.len() - pos).min(MAX_MATCH);
+    let mut best: Option<(usize, usize)> = None;
+    for &start in candidates.iter().rev().take(MAX_CHAIN) {
+        if pos - start > WINDOW_SIZE {
+            break;
+        }
+        let length = (0..limit)
+            .take_while(|i| data[start + i] == data[pos + i])
+            .count();
+        if length >= MIN_MATCH && best.is_none_or(|(l, _)| length > l) {
+            best = Some((length, pos - start));
+            if length == limit {
+                break;
+            }
+        }
+    }
+    best
+}
+
+/// Compresses `input` into a raw DEFLATE stream.
+pub fn deflate(input: &ByteStream) -> ByteStream {
+    let data = input.clone().into_bytes();
+    let mut writer = BitWriter::new();
+    // BFINAL, then BTYPE = 01 for fixed Huffman codes.
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut chains: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let found = if pos + MIN_MATCH <= data.len() {
+            chains
+                .get(&data[pos..pos + MIN_MATCH])
+                .and_then(|candidates| longest_match(&data, pos, candidates))
+        } else {
+            None
+        };
+        let step = match found {
+            Some((length, distance)) => {
+                write_match(&mut writer, length, distance);
+                length
+            }
+            None => {
+                write_literal(&mut writer, u16::from(data[pos]));
+                1
+            }
+        };
+        for i in pos..pos + step {
+            if i + MIN_MATCH <= data.len() {
+                chains.entry(&data[i..i + MIN_MATCH]).or_default().push(i);
+            }
+        }
+        pos += step;
+    }
+    write_literal(&mut writer, END_OF_BLOCK);
+    ByteStream::from(writer.into_bytes())
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Compresses `input` into a zlib stream: a two byte header, the DEFLATE
+/// stream, and the Adler-32 checksum of the input.
+pub fn zlib_compress(input: &ByteStream) -> ByteStream {
+    // 32KiB window, deflate, fastest compression level.
+    let mut output = ByteStream::from_bytes(&[0x78, 0x01]);
+    output.append(&mut deflate(input));
+    output.append(&mut ByteStream::from_bytes(
+        &adler32(&input.clone().into_bytes()).to_be_bytes(),
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the single fixed Huffman block that `deflate` produces.
+    fn inflate(stream: &ByteStream) -> Vec<u8> {
+        let bytes = stream.clone().into_bytes();
+        let mut bit = 0;
+        let mut read_bits = |count: u8| {
+            let mut value = 0u32;
+            for i in 0..count {
+                value |= u32::from((bytes[bit / 8] >> (bit % 8)) & 1) << i;
+                bit += 1;
+            }
+            value
+        };
+        assert_eq!(read_bits(3), 0b011);
+
+        let mut output: Vec<u8> = Vec::new();
+        loop {
+            let mut code = 0u16;
+            let mut length = 0;
+            let symbol = loop {
+                code = (code << 1) | read_bits(1) as u16;
+                length += 1;
+                if let Some(symbol) = (0..288).find(|s| fixed_literal_code(*s) == (code, length)) {
+                    break symbol;
+                }
+            };
+            match symbol {
+                0..=255 => output.push(symbol as u8),
+                END_OF_BLOCK => return output,
+                _ => {
+                    let i = usize::from(symbol - 257);
+                    let length =
+                        usize::from(LENGTH_BASES[i]) + read_bits(LENGTH_EXTRA_BITS[i]) as usize;
+                    let mut d = 0u16;
+                    for _ in 0..5 {
+                        d = (d << 1) | read_bits(1) as u16;
+                    }
+                    let d = usize::from(d);
+                    let distance =
+                        usize::from(DISTANCE_BASES[d]) + read_bits(DISTANCE_EXTRA_BITS[d]) as usize;
+                    for _ in 0..length {
+                        let byte = output[output.len() - distance];
+                        output.push(byte);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_round_trips_through_deflate() {
+        let inputs = vec![
+            Vec::new(),
+            b"a".to_vec(),
+            b"abcabcabcabcabcabcabcabcabcabc".to_vec(),
+            vec![0; 1000],
+            ByteStream::random(500).into_bytes(),
+            b"Never reveal the Wu-Tang Secret! Never reveal the Wu-Tang Secret!".to_vec(),
+        ];
+        for input in inputs {
+            assert_eq!(inflate(&deflate(&ByteStream::from(input.clone()))), input);
+        }
+    }
+
+    #[test]
+    fn it_shrinks_repetitive_input() {
+        let repetitive = ByteStream::from_ascii(&"sessionid=".repeat(20)).unwrap();
+        assert!(deflate(&repetitive).len() < 30);
+        let random = ByteStream::random(200);
+        assert!(deflate(&random).len() > 200);
+    }
+
+    #[test]
+    fn it_wraps_in_zlib() {
+        assert_eq!(
+            zlib_compress(&ByteStream::new()).into_hex(),
+            "7801030000000001"
+        );
+        assert_eq!(
+            zlib_compress(&ByteStream::from_ascii("a").unwrap()).into_hex(),
+            "78014b040000620062"
+        );
+    }
+}
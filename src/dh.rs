@@ -0,0 +1,246 @@
+//!
+//! Finite-field Diffie-Hellman key exchange over the RFC 3526 MODP groups.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use hash::Hash;
+
+/// The length in bytes of the AES-128 keys produced by `derive_key`.
+pub const AES_KEY_SIZE: usize = 16;
+
+const MODP_1536: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff",
+);
+
+const MODP_2048: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b",
+    "e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718",
+    "3995497cea956ae515d2261898fa051015728e5a8aacaa68ffffffffffffffff",
+);
+
+const MODP_3072: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b",
+    "e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718",
+    "3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33",
+    "a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7",
+    "abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864",
+    "d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2",
+    "08e24fa074e5ab3143db5bfce0fd108e4b82d120a93ad2caffffffffffffffff",
+);
+
+const MODP_4096: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b",
+    "e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718",
+    "3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33",
+    "a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7",
+    "abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864",
+    "d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2",
+    "08e24fa074e5ab3143db5bfce0fd108e4b82d120a92108011a723c12a787e6d7",
+    "88719a10bdba5b2699c327186af4e23c1a946834b6150bda2583e9ca2ad44ce8",
+    "dbbbc2db04de8ef92e8efc141fbecaa6287c59474e6bc05d99b2964fa090c3a2",
+    "233ba186515be7ed1f612970cee2d7afb81bdd762170481cd0069127d5b05aa9",
+    "93b4ea988d8fddc186ffb7dc90a6c08f4df435c934063199ffffffffffffffff",
+);
+
+const MODP_6144: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b",
+    "e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718",
+    "3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33",
+    "a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7",
+    "abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864",
+    "d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2",
+    "08e24fa074e5ab3143db5bfce0fd108e4b82d120a92108011a723c12a787e6d7",
+    "88719a10bdba5b2699c327186af4e23c1a946834b6150bda2583e9ca2ad44ce8",
+    "dbbbc2db04de8ef92e8efc141fbecaa6287c59474e6bc05d99b2964fa090c3a2",
+    "233ba186515be7ed1f612970cee2d7afb81bdd762170481cd0069127d5b05aa9",
+    "93b4ea988d8fddc186ffb7dc90a6c08f4df435c93402849236c3fab4d27c7026",
+    "c1d4dcb2602646dec9751e763dba37bdf8ff9406ad9e530ee5db382f413001ae",
+    "b06a53ed9027d831179727b0865a8918da3edbebcf9b14ed44ce6cbaced4bb1b",
+    "db7f1447e6cc254b332051512bd7af426fb8f401378cd2bf5983ca01c64b92ec",
+    "f032ea15d1721d03f482d7ce6e74fef6d55e702f46980c82b5a84031900b1c9e",
+    "59e7c97fbec7e8f323a97a7e36cc88be0f1d45b7ff585ac54bd407b22b4154aa",
+    "cc8f6d7ebf48e1d814cc5ed20f8037e0a79715eef29be32806a1d58bb7c5da76",
+    "f550aa3d8a1fbff0eb19ccb1a313d55cda56c9ec2ef29632387fe8d76e3c0468",
+    "043e8f663f4860ee12bf2d5b0b7474d6e694f91e6dcc4024ffffffffffffffff",
+);
+
+const MODP_8192: &str = concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74",
+    "020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437",
+    "4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed",
+    "ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05",
+    "98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb",
+    "9ed529077096966d670c354e4abc9804f1746c08ca18217c32905e462e36ce3b",
+    "e39e772c180e86039b2783a2ec07a28fb5c55df06f4c52c9de2bcbf695581718",
+    "3995497cea956ae515d2261898fa051015728e5a8aaac42dad33170d04507a33",
+    "a85521abdf1cba64ecfb850458dbef0a8aea71575d060c7db3970f85a6e1e4c7",
+    "abf5ae8cdb0933d71e8c94e04a25619dcee3d2261ad2ee6bf12ffa06d98a0864",
+    "d87602733ec86a64521f2b18177b200cbbe117577a615d6c770988c0bad946e2",
+    "08e24fa074e5ab3143db5bfce0fd108e4b82d120a92108011a723c12a787e6d7",
+    "88719a10bdba5b2699c327186af4e23c1a946834b6150bda2583e9ca2ad44ce8",
+    "dbbbc2db04de8ef92e8efc141fbecaa6287c59474e6bc05d99b2964fa090c3a2",
+    "233ba186515be7ed1f612970cee2d7afb81bdd762170481cd0069127d5b05aa9",
+    "93b4ea988d8fddc186ffb7dc90a6c08f4df435c93402849236c3fab4d27c7026",
+    "c1d4dcb2602646dec9751e763dba37bdf8ff9406ad9e530ee5db382f413001ae",
+    "b06a53ed9027d831179727b0865a8918da3edbebcf9b14ed44ce6cbaced4bb1b",
+    "db7f1447e6cc254b332051512bd7af426fb8f401378cd2bf5983ca01c64b92ec",
+    "f032ea15d1721d03f482d7ce6e74fef6d55e702f46980c82b5a84031900b1c9e",
+    "59e7c97fbec7e8f323a97a7e36cc88be0f1d45b7ff585ac54bd407b22b4154aa",
+    "cc8f6d7ebf48e1d814cc5ed20f8037e0a79715eef29be32806a1d58bb7c5da76",
+    "f550aa3d8a1fbff0eb19ccb1a313d55cda56c9ec2ef29632387fe8d76e3c0468",
+    "043e8f663f4860ee12bf2d5b0b7474d6e694f91e6dbe115974a3926f12fee5e4",
+    "38777cb6a932df8cd8bec4d073b931ba3bc832b68d9dd300741fa7bf8afc47ed",
+    "2576f6936ba424663aab639c5ae4f5683423b4742bf1c978238f16cbe39d652d",
+    "e3fdb8befc848ad922222e04a4037c0713eb57a81a23f0c73473fc646cea306b",
+    "4bcbc8862f8385ddfa9d4b7fa2c087e879683303ed5bdd3a062b3cf5b3a278a6",
+    "6d2a13f83f44f82ddf310ee074ab6a364597e899a0255dc164f31cc50846851d",
+    "f9ab48195ded7ea1b1d510bd7ee74d73faf36bc31ecfa268359046f4eb879f92",
+    "4009438b481c6cd7889a002ed5ee382bc9190da6fc026e479558e4475677e9aa",
+    "9e3050e2765694dfc81f56e880b96e7160c980dd98edd3dfffffffffffffffff",
+);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    pub p: BigUint,
+    pub g: BigUint,
+}
+
+impl Group {
+    pub fn new(p: BigUint, g: BigUint) -> Group {
+        Group { p, g }
+    }
+
+    /// The RFC 3526 MODP group with a `bits`-bit prime, if there is one.
+    pub fn modp(bits: usize) -> Option<Group> {
+        let p = match bits {
+            1536 => MODP_1536,
+            2048 => MODP_2048,
+            3072 => MODP_3072,
+            4096 => MODP_4096,
+            6144 => MODP_6144,
+            8192 => MODP_8192,
+            _ => return None,
+        };
+        Some(Group::new(
+            BigUint::from_hex(p).unwrap(),
+            BigUint::from(2u64),
+        ))
+    }
+
+    /// Picks a private exponent in `[1, p - 1)` and the matching public value.
+    pub fn generate_keypair(&self) -> Keypair {
+        let private = BigUint::random_below(&(&self.p - BigUint::from(2u64))) + BigUint::one();
+        self.keypair_from_private(private)
+    }
+
+    pub fn keypair_from_private(&self, private: BigUint) -> Keypair {
+        let public = self.g.modpow(&private, &self.p);
+        Keypair { private, public }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keypair {
+    pub private: BigUint,
+    pub public: BigUint,
+}
+
+impl Keypair {
+    pub fn shared_secret(&self, group: &Group, other_public: &BigUint) -> BigUint {
+        other_public.modpow(&self.private, &group.p)
+    }
+}
+
+/// Derives an AES-128 key by hashing the big-endian shared secret and keeping
+/// the first `AES_KEY_SIZE` bytes of the digest.
+pub fn derive_key<H: Hash>(secret: &BigUint) -> ByteStream {
+    assert!(H::OUTPUT_SIZE >= AES_KEY_SIZE);
+    let digest = H::digest(&secret.to_byte_stream());
+    ByteStream::from_bytes(&digest.into_bytes()[..AES_KEY_SIZE])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha1::Sha1;
+    use sha2::Sha256;
+
+    #[test]
+    fn it_agrees_on_a_secret_in_a_small_group() {
+        let group = Group::new(BigUint::from(37u64), BigUint::from(5u64));
+        for _ in 0..10 {
+            let a = group.generate_keypair();
+            let b = group.generate_keypair();
+            assert!(a.private >= BigUint::one() && a.private < group.p);
+            assert_eq!(
+                a.shared_secret(&group, &b.public),
+                b.shared_secret(&group, &a.public)
+            );
+        }
+    }
+
+    #[test]
+    fn it_agrees_on_a_secret_in_a_modp_group() {
+        let group = Group::modp(1536).unwrap();
+        assert!(group
+            .p
+            .to_hex()
+            .starts_with("ffffffffffffffffc90fdaa22168c234"));
+        assert!(group.p.to_hex().ends_with("ca237327ffffffffffffffff"));
+
+        let a = group.generate_keypair();
+        let b = group.generate_keypair();
+        let secret = a.shared_secret(&group, &b.public);
+        assert_eq!(secret, b.shared_secret(&group, &a.public));
+
+        let key = derive_key::<Sha256>(&secret);
+        assert_eq!(key.len(), AES_KEY_SIZE);
+        assert_eq!(key, derive_key::<Sha256>(&secret));
+        assert!(key != derive_key::<Sha1>(&secret));
+    }
+
+    #[test]
+    fn it_knows_every_rfc_3526_group() {
+        for &bits in &[1536, 2048, 3072, 4096, 6144, 8192] {
+            let group = Group::modp(bits).unwrap();
+            assert_eq!(group.p.bits(), bits);
+            assert_eq!(group.g, BigUint::from(2u64));
+        }
+        assert_eq!(Group::modp(1024), None);
+    }
+
+    #[test]
+    fn it_derives_keys_by_truncating_a_hash() {
+        let secret = BigUint::from(0x0102u64);
+        assert_eq!(
+            derive_key::<Sha1>(&secret).into_hex(),
+            "0ca623e2855f2c75c842ad302fe820e4"
+        );
+    }
+}
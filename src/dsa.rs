@@ -0,0 +1,403 @@
+//!
+//! DSA signatures, and recovering the private key when the per-signature
+//! nonce is known, guessable or repeated, along with the forgeries that
+//! tampered generators allow.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use hash::Hash;
+use std::ops::Range;
+
+const P: &str = concat!(
+    "800000000000000089e1855218a0e7dac38136ffafa72eda7859f2171e25e65e",
+    "ac698c1702578b07dc2a1076da241c76c62d374d8389ea5aeffd3226a0530cc5",
+    "65f3bf6b50929139ebeac04f48c3c84afb796d61e5a4f9a8fda812ab59494232",
+    "c7d2b4deb50aa18ee9e132bfa85ac4374d7f9091abc3d015efc871a584471bb1",
+);
+
+const Q: &str = "f4f47f05794b256174bba6e9b396a7707e563c5b";
+
+const G: &str = concat!(
+    "5958c9d3898b224b12672c0b98e06c60df923cb8bc999d119458fef538b8fa40",
+    "46c8db53039db620c094c9fa077ef389b5322a559946a71903f990f1f7e0e025",
+    "e2d7f7cf494aff1a0470f5b64c36b625a097f1651fe775323556fe00b3608c88",
+    "7892878480e99041be601a62166ca6894bdd41a7054ec89f756ba9fc95302291",
+);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameters {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keypair {
+    pub private: BigUint,
+    pub public: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+impl Parameters {
+    pub fn new(p: BigUint, q: BigUint, g: BigUint) -> Parameters {
+        Parameters { p, q, g }
+    }
+
+    /// A fixed 1024-bit group with a 160-bit subgroup.
+    pub fn default_1024() -> Parameters {
+        Parameters::new(
+            BigUint::from_hex(P).unwrap(),
+            BigUint::from_hex(Q).unwrap(),
+            BigUint::from_hex(G).unwrap(),
+        )
+    }
+
+    /// Generates an `l`-bit prime `p` with an `n`-bit prime `q` dividing
+    /// `p - 1`, and a generator of the order-`q` subgroup.
+    pub fn generate(l: usize, n: usize) -> Parameters {
+        assert!(l > n, "p must be larger than q");
+        let one = BigUint::one();
+        let q = BigUint::random_prime(n);
+        let two_q = &q * BigUint::from(2u64);
+        let top = BigUint::one() << (l - 1);
+        let p = loop {
+            let x = BigUint::random_below(&top) + &top;
+            let p = &x - (&x % &two_q) + &one;
+            if p.bits() == l && p.is_probable_prime(40) {
+                break p;
+            }
+        };
+
+        let exponent = (&p - &one) / &q;
+        let mut h = BigUint::from(2u64);
+        loop {
+            let g = h.modpow(&exponent, &p);
+            if !g.is_one() {
+                return Parameters::new(p, q, g);
+            }
+            h = h + &one;
+        }
+    }
+
+    /// Picks a private key in `[1, q)`.
+    pub fn generate_keypair(&self) -> Keypair {
+        let private = BigUint::random_below(&(&self.q - BigUint::one())) + BigUint::one();
+        self.keypair_from_private(private)
+    }
+
+    pub fn keypair_from_private(&self, private: BigUint) -> Keypair {
+        let public = self.g.modpow(&private, &self.p);
+        Keypair { private, public }
+    }
+
+    /// The leftmost bits of `H(message)`, as many as `q` has.
+    pub fn hash_message<H: Hash>(&self, message: &ByteStream) -> BigUint {
+        let z = BigUint::from_byte_stream(&H::digest(message));
+        let digest_bits = H::OUTPUT_SIZE * 8;
+        if digest_bits > self.q.bits() {
+            z >> (digest_bits - self.q.bits())
+        } else {
+            z
+        }
+    }
+
+    /// Checks `0 < r < q` and `0 < s < q` before verifying.
+    pub fn verify<H: Hash>(
+        &self,
+        public: &BigUint,
+        message: &ByteStream,
+        signature: &Signature,
+    ) -> bool {
+        let in_range = |x: &BigUint| !x.is_zero() && *x < self.q;
+        in_range(&signature.r)
+            && in_range(&signature.s)
+            && self.verify_without_range_check::<H>(public, message, signature)
+    }
+
+    /// Verifies without checking that `r` and `s` lie in `(0, q)`, as a
+    /// careless implementation might.
+    pub fn verify_without_range_check<H: Hash>(
+        &self,
+        public: &BigUint,
+        message: &ByteStream,
+        signature: &Signature,
+    ) -> bool {
+        let Signature { ref r, ref s } = *signature;
+        let w = match s.modinv(&self.q) {
+            Some(w) => w,
+            None => return false,
+        };
+        let z = self.hash_message::<H>(message);
+        let u1 = z * &w % &self.q;
+        let u2 = r * &w % &self.q;
+        let v = self.g.modpow(&u1, &self.p) * public.modpow(&u2, &self.p) % &self.p % &self.q;
+        v == r % &self.q
+    }
+}
+
+impl Keypair {
+    pub fn sign<H: Hash>(&self, params: &Parameters, message: &ByteStream) -> Signature {
+        loop {
+            let k = BigUint::random_below(&(&params.q - BigUint::one())) + BigUint::one();
+            if let Some(signature) = self.sign_with_nonce::<H>(params, message, &k) {
+                return signature;
+            }
+        }
+    }
+
+    /// Signs with the given nonce, or returns `None` if it produces a zero
+    /// `s`. A zero `r` is let through so that tampered generators can be
+    /// demonstrated.
+    pub fn sign_with_nonce<H: Hash>(
+        &self,
+        params: &Parameters,
+        message: &ByteStream,
+        k: &BigUint,
+    ) -> Option<Signature> {
+        let r = params.g.modpow(k, &params.p) % &params.q;
+        let z = params.hash_message::<H>(message);
+        let s = k.modinv(&params.q)? * (z + &self.private * &r) % &params.q;
+        if s.is_zero() {
+            None
+        } else {
+            Some(Signature { r, s })
+        }
+    }
+}
+
+fn sub_mod(a: &BigUint, b: &BigUint, m: &BigUint) -> BigUint {
+    (a % m + m - b % m) % m
+}
+
+/// Recovers the private key from a signature and the nonce it used:
+/// `x = (s * k - H(m)) / r mod q`.
+pub fn private_key_from_nonce<H: Hash>(
+    params: &Parameters,
+    message: &ByteStream,
+    signature: &Signature,
+    k: &BigUint,
+) -> Option<BigUint> {
+    let z = params.hash_message::<H>(message);
+    let sk = &signature.s * k % &params.q;
+    Some(sub_mod(&sk, &z, &params.q) * signature.r.modinv(&params.q)? % &params.q)
+}
+
+/// Tries every nonce in `nonces`, walking `g^k` forward one multiplication
+/// at a time and only solving for the key when it matches `r`.
+pub fn recover_private_key_brute_force<H: Hash>(
+    params: &Parameters,
+    public: &BigUint,
+    message: &ByteStream,
+    signature: &Signature,
+    nonces: Range<u64>,
+) -> Option<BigUint> {
+    let r = &signature.r;
+    let mut g_k = params.g.modpow(&BigUint::from(nonces.start), &params.p);
+    for k in nonces {
+        if &g_k % &params.q == *r {
+            let k = BigUint::from(k);
+            if let Some(x) = private_key_from_nonce::<H>(params, message, signature, &k) {
+                if params.g.modpow(&x, &params.p) == *public {
+                    return Some(x);
+                }
+            }
+        }
+        g_k = g_k * &params.g % &params.p;
+    }
+    None
+}
+
+/// Recovers the private key from two signatures that share a nonce, which
+/// shows up as a shared `r`: `k = (H(m1) - H(m2)) / (s1 - s2) mod q`.
+pub fn recover_private_key_repeated_nonce<H: Hash>(
+    params: &Parameters,
+    first: (&ByteStream, &Signature),
+    second: (&ByteStream, &Signature),
+) -> Option<BigUint> {
+    let (m1, sig1) = first;
+    let (m2, sig2) = second;
+    let q = &params.q;
+    let dz = sub_mod(
+        &params.hash_message::<H>(m1),
+        &params.hash_message::<H>(m2),
+        q,
+    );
+    let ds = sub_mod(&sig1.s, &sig2.s, q);
+    let k = dz * ds.modinv(q)? % q;
+    private_key_from_nonce::<H>(params, m1, sig1, &k)
+}
+
+/// Searches a batch of signatures from one key for a pair with a repeated
+/// nonce, and returns the private key it gives away.
+pub fn find_repeated_nonce<H: Hash>(
+    params: &Parameters,
+    public: &BigUint,
+    signed: &[(ByteStream, Signature)],
+) -> Option<BigUint> {
+    for (i, (m1, sig1)) in signed.iter().enumerate() {
+        for (m2, sig2) in signed.iter().skip(i + 1) {
+            if sig1.r != sig2.r {
+                continue;
+            }
+            if let Some(x) = recover_private_key_repeated_nonce::<H>(params, (m1, sig1), (m2, sig2))
+            {
+                if params.g.modpow(&x, &params.p) == *public {
+                    return Some(x);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// With the generator replaced by `p + 1`, which is 1 mod `p`, this
+/// signature verifies for every message: `r = (y^z mod p) mod q` and
+/// `s = r / z mod q` for a random `z`.
+pub fn forge_with_one_generator(params: &Parameters, public: &BigUint) -> Signature {
+    loop {
+        let z = BigUint::random_below(&(&params.q - BigUint::one())) + BigUint::one();
+        let r = public.modpow(&z, &params.p) % &params.q;
+        if let Some(z_inv) = z.modinv(&params.q) {
+            let s = &r * z_inv % &params.q;
+            if !r.is_zero() && !s.is_zero() {
+                return Signature { r, s };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha1::{sha1, Sha1};
+
+    fn message(s: &str) -> ByteStream {
+        ByteStream::from_ascii(s).unwrap()
+    }
+
+    #[test]
+    fn it_signs_and_verifies() {
+        let params = Parameters::default_1024();
+        let keypair = params.generate_keypair();
+        let signature = keypair.sign::<Sha1>(&params, &message("hello"));
+
+        assert!(params.verify::<Sha1>(&keypair.public, &message("hello"), &signature));
+        assert!(!params.verify::<Sha1>(&keypair.public, &message("goodbye"), &signature));
+        let other = params.generate_keypair();
+        assert!(!params.verify::<Sha1>(&other.public, &message("hello"), &signature));
+    }
+
+    #[test]
+    fn it_generates_parameters() {
+        let params = Parameters::generate(256, 64);
+        assert_eq!(params.p.bits(), 256);
+        assert_eq!(params.q.bits(), 64);
+        assert!((&params.p - BigUint::one()) % &params.q == BigUint::zero());
+        assert!(params.g.modpow(&params.q, &params.p).is_one());
+
+        let keypair = params.generate_keypair();
+        let signature = keypair.sign::<Sha1>(&params, &message("hello"));
+        assert!(params.verify::<Sha1>(&keypair.public, &message("hello"), &signature));
+    }
+
+    #[test]
+    fn it_recovers_a_key_from_a_guessable_nonce() {
+        let params = Parameters::default_1024();
+        let public = BigUint::from_hex(concat!(
+            "84ad4719d044495496a3201c8ff484feb45b962e7302e56a392aee4abab3e4bd",
+            "ebf2955b4736012f21a08084056b19bcd7fee56048e004e44984e2f411788efd",
+            "c837a0d2e5abb7b555039fd243ac01f0fb2ed1dec568280ce678e931868d23eb",
+            "095fde9d3779191b8c0299d6e07bbb283e6633451e535c45513b2d33c99ea17",
+        ))
+        .unwrap();
+        let signed = message(concat!(
+            "For those that envy a MC it can be hazardous to your health\n",
+            "So be friendly, a matter of life and death, just like a etch-a-sketch\n",
+        ));
+        let signature = Signature {
+            r: BigUint::from_decimal("548099063082341131477253921760299949438196259240").unwrap(),
+            s: BigUint::from_decimal("857042759984254168557880549501802188789837994940").unwrap(),
+        };
+        assert!(params.verify::<Sha1>(&public, &signed, &signature));
+
+        let x = recover_private_key_brute_force::<Sha1>(
+            &params,
+            &public,
+            &signed,
+            &signature,
+            0..1 << 16,
+        )
+        .unwrap();
+        assert_eq!(
+            sha1(&message(&x.to_hex())).into_hex(),
+            "0954edd5e0afe5542a4adf012611a91912a3ec16"
+        );
+    }
+
+    #[test]
+    fn it_recovers_a_key_from_a_repeated_nonce() {
+        let params = Parameters::default_1024();
+        let keypair = params.generate_keypair();
+        let k = BigUint::from(0xdead_beef_u64);
+        let mut signed: Vec<(ByteStream, Signature)> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|m| (message(m), keypair.sign::<Sha1>(&params, &message(m))))
+            .collect();
+        assert_eq!(
+            find_repeated_nonce::<Sha1>(&params, &keypair.public, &signed),
+            None
+        );
+
+        for m in &["delta", "epsilon"] {
+            let signature = keypair
+                .sign_with_nonce::<Sha1>(&params, &message(m), &k)
+                .unwrap();
+            signed.push((message(m), signature));
+        }
+        assert_eq!(
+            find_repeated_nonce::<Sha1>(&params, &keypair.public, &signed),
+            Some(keypair.private)
+        );
+    }
+
+    #[test]
+    fn it_forges_with_a_zero_generator() {
+        let honest = Parameters::default_1024();
+        let tampered = Parameters::new(honest.p.clone(), honest.q.clone(), BigUint::zero());
+        let keypair = honest.generate_keypair();
+        let signature = keypair.sign::<Sha1>(&tampered, &message("Hello, world"));
+        assert!(signature.r.is_zero());
+
+        for m in &["Hello, world", "Goodbye, world"] {
+            assert!(tampered.verify_without_range_check::<Sha1>(
+                &keypair.public,
+                &message(m),
+                &signature
+            ));
+            assert!(!tampered.verify::<Sha1>(&keypair.public, &message(m), &signature));
+        }
+    }
+
+    #[test]
+    fn it_forges_with_a_generator_of_p_plus_one() {
+        let honest = Parameters::default_1024();
+        let tampered = Parameters::new(
+            honest.p.clone(),
+            honest.q.clone(),
+            &honest.p + BigUint::one(),
+        );
+        let keypair = honest.generate_keypair();
+        let signature = forge_with_one_generator(&tampered, &keypair.public);
+
+        for m in &["Hello, world", "Goodbye, world"] {
+            assert!(tampered.verify::<Sha1>(&keypair.public, &message(m), &signature));
+            assert!(!honest.verify::<Sha1>(&keypair.public, &message(m), &signature));
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//!
+//! Kelsey and Schneier's second preimages for long messages: an expandable
+//! message reaches the same state at any length in a range, which defeats the
+//! length padding that otherwise stops a second preimage from bridging into
+//! the middle of the target.
+//!
+
+use byte_stream::ByteStream;
+use std::collections::HashMap;
+use toy_hash::{ToyHash, BLOCK_SIZE};
+
+/// A block from `a` and a block from `b` that reach the same state, found by
+/// the birthday paradox. Returns both blocks and that state.
+pub fn find_collision_between(hash: &ToyHash, a: u32, b: u32) -> (ByteStream, ByteStream, u32) {
+    let mut from_a: HashMap<u32, ByteStream> = HashMap::new();
+    let mut from_b: HashMap<u32, ByteStream> = HashMap::new();
+    loop {
+        let block = ByteStream::random(BLOCK_SIZE);
+        let bytes = block.clone().into_bytes();
+        let (next_a, next_b) = (hash.compress(a, &bytes), hash.compress(b, &bytes));
+        if let Some(other) = from_b.get(&next_a) {
+            return (block, other.clone(), next_a);
+        }
+        if let Some(other) = from_a.get(&next_b) {
+            return (other.clone(), block, next_b);
+        }
+        from_a.insert(next_a, block.clone());
+        from_b.insert(next_b, block);
+    }
+}
+
+/// `k` pairs of colliding messages, the `i`th made of one block and of
+/// `2^(k - 1 - i) + 1` blocks, which together give a message of any length
+/// from `k` to `k + 2^k - 1` blocks ending in the same state.
+pub struct ExpandableMessage {
+    pub pairs: Vec<(ByteStream, ByteStream)>,
+    pub state: u32,
+}
+
+impl ExpandableMessage {
+    pub fn generate(hash: &ToyHash, state: u32, k: usize) -> ExpandableMessage {
+        let mut pairs = Vec::with_capacity(k);
+        let mut state = state;
+        for i in 0..k {
+            let mut long = ByteStream::from(vec![0; BLOCK_SIZE << (k - 1 - i)]);
+            let dummy_state = hash.iterate(state, &long);
+            let (short, mut last, next) = find_collision_between(hash, state, dummy_state);
+            long.append(&mut last);
+            pairs.push((short, long));
+            state = next;
+        }
+        ExpandableMessage { pairs, state }
+    }
+
+    /// The shortest and longest messages available, in blocks.
+    pub fn range(&self) -> (usize, usize) {
+        let k = self.pairs.len();
+        (k, k + (1 << k) - 1)
+    }
+
+    /// A message of `blocks` blocks, if that is within `range`.
+    pub fn message(&self, blocks: usize) -> Option<ByteStream> {
+        let (min, max) = self.range();
+        if blocks < min || blocks > max {
+            return None;
+        }
+        let k = self.pairs.len();
+        let extra = blocks - min;
+        let mut message = ByteStream::new();
+        for (i, (short, long)) in self.pairs.iter().enumerate() {
+            let part = if extra >> (k - 1 - i) & 1 == 1 {
+                long
+            } else {
+                short
+            };
+            message.append(&mut part.clone());
+        }
+        Some(message)
+    }
+}
+
+/// Finds a different message of the same length and digest as `message`.
+///
+/// With `2^k` blocks in the target, an expandable message covering lengths up
+/// to about `2^k` is built, then a bridge block is searched for that takes its
+/// final state to any of the target's intermediate states; that costs about
+/// `2^(b - k)` compressions for a `b`-bit state rather than `2^b`. Returns
+/// `None` for messages of fewer than two blocks, or if no bridge turns up.
+pub fn second_preimage(hash: &ToyHash, message: &ByteStream) -> Option<ByteStream> {
+    let bytes = message.clone().into_bytes();
+    let blocks = bytes.len() / BLOCK_SIZE;
+    if blocks < 2 {
+        return None;
+    }
+    let k = (usize::BITS - 1 - blocks.leading_zeros()) as usize;
+    let expandable = ExpandableMessage::generate(hash, hash.initial_state(), k);
+    let (min, max) = expandable.range();
+
+    // The state after each prefix of `j` blocks that a bridge could land on.
+    let mut targets: HashMap<u32, usize> = HashMap::new();
+    let mut state = hash.initial_state();
+    for (i, block) in bytes.chunks_exact(BLOCK_SIZE).enumerate() {
+        state = hash.compress(state, block);
+        let j = i + 1;
+        if j > min && j - 1 <= max {
+            targets.insert(state, j);
+        }
+    }
+
+    for _ in 0..16u64 << hash.bits() {
+        let bridge = ByteStream::random(BLOCK_SIZE);
+        let next = hash.compress(expandable.state, &bridge.clone().into_bytes());
+        if let Some(&j) = targets.get(&next) {
+            let mut forged = expandable.message(j - 1)?;
+            forged.append(&mut bridge.clone());
+            forged.append(&mut ByteStream::from_bytes(&bytes[j * BLOCK_SIZE..]));
+            return Some(forged);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_expands_to_every_length_in_range() {
+        let hash = ToyHash::new(2);
+        let expandable = ExpandableMessage::generate(&hash, hash.initial_state(), 4);
+        assert_eq!(expandable.range(), (4, 19));
+        for blocks in 4..20 {
+            let message = expandable.message(blocks).unwrap();
+            assert_eq!(message.len(), blocks * BLOCK_SIZE);
+            assert_eq!(
+                hash.iterate(hash.initial_state(), &message),
+                expandable.state
+            );
+        }
+        assert_eq!(expandable.message(3), None);
+        assert_eq!(expandable.message(20), None);
+    }
+
+    #[test]
+    fn it_finds_a_second_preimage_for_a_long_message() {
+        let hash = ToyHash::new(3);
+        let k = 10;
+        let message = ByteStream::random(BLOCK_SIZE << k);
+        let forged = second_preimage(&hash, &message).unwrap();
+
+        assert!(forged != message);
+        assert_eq!(forged.len(), message.len());
+        assert_eq!(hash.digest(&forged), hash.digest(&message));
+    }
+}
@@ -0,0 +1,22 @@
+//!
+//! The interface shared by the crate's hash functions.
+//!
+
+use byte_stream::ByteStream;
+
+pub trait Hash: Sized {
+    /// The size in bytes of the blocks the compression function consumes.
+    const BLOCK_SIZE: usize;
+    /// The size in bytes of a digest.
+    const OUTPUT_SIZE: usize;
+
+    fn new() -> Self;
+    fn update(&mut self, bs: &ByteStream);
+    fn finalize(self) -> ByteStream;
+
+    fn digest(bs: &ByteStream) -> ByteStream {
+        let mut hasher = Self::new();
+        hasher.update(bs);
+        hasher.finalize()
+    }
+}
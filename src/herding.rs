@@ -0,0 +1,227 @@
+//!
+//! Kelsey and Kohno's herding ("Nostradamus") attack: commit to a digest
+//! first, then for any prefix chosen afterwards find a suffix that brings the
+//! whole message to that digest, through a diamond structure of collisions.
+//!
+
+use byte_stream::ByteStream;
+use expandable_message::find_collision_between;
+use std::collections::{HashMap, HashSet};
+use toy_hash::{ToyHash, BLOCK_SIZE};
+
+/// A binary tree of collisions: `2^k` leaf states, each pair of which is
+/// joined by a pair of blocks colliding into a state on the next level, down
+/// to a single state.
+pub struct Diamond {
+    leaves: Vec<u32>,
+    /// `levels[d][i]` takes node `i` of level `d` to node `i / 2` of level
+    /// `d + 1`; level 0 is the leaves.
+    levels: Vec<Vec<ByteStream>>,
+    pub state: u32,
+}
+
+impl Diamond {
+    /// A diamond structure over `2^k` distinct random leaves.
+    pub fn generate(hash: &ToyHash, k: usize) -> Diamond {
+        assert!(k < hash.bits());
+        let mut distinct = HashSet::new();
+        let mut leaves = Vec::with_capacity(1 << k);
+        while leaves.len() < 1 << k {
+            let leaf = ByteStream::random(4)
+                .iter()
+                .fold(0u32, |acc, b| (acc << 8) | u32::from(*b))
+                >> (32 - hash.bits());
+            if distinct.insert(leaf) {
+                leaves.push(leaf);
+            }
+        }
+
+        let mut levels = Vec::with_capacity(k);
+        let mut nodes = leaves.clone();
+        while nodes.len() > 1 {
+            let mut blocks = Vec::with_capacity(nodes.len());
+            let mut next = Vec::with_capacity(nodes.len() / 2);
+            for pair in nodes.chunks(2) {
+                let (first, second, state) = find_collision_between(hash, pair[0], pair[1]);
+                blocks.push(first);
+                blocks.push(second);
+                next.push(state);
+            }
+            levels.push(blocks);
+            nodes = next;
+        }
+        Diamond {
+            leaves,
+            levels,
+            state: nodes[0],
+        }
+    }
+
+    /// The depth `k` of a structure with `2^k` leaves.
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn leaves(&self) -> &[u32] {
+        &self.leaves
+    }
+
+    /// The blocks that take leaf `leaf` to the final state.
+    pub fn path(&self, leaf: usize) -> ByteStream {
+        let mut path = ByteStream::new();
+        for (d, blocks) in self.levels.iter().enumerate() {
+            path.append(&mut blocks[leaf >> d].clone());
+        }
+        path
+    }
+
+    /// The digest to commit to ahead of time, which every message herded
+    /// from a prefix of `prefix_blocks` blocks will have.
+    pub fn commitment(&self, hash: &ToyHash, prefix_blocks: usize) -> Commitment {
+        let message_len = (prefix_blocks + 1 + self.depth()) * BLOCK_SIZE;
+        let state = hash.iterate(self.state, &ToyHash::padding(message_len));
+        Commitment {
+            digest: ByteStream::from_bytes(&state.to_be_bytes()[4 - hash.bits() / 8..]),
+            prefix_blocks,
+        }
+    }
+}
+
+/// A digest published in advance, along with the prefix length it was made
+/// for: the padding covers the whole message, so only prefixes of exactly
+/// that many blocks can be herded to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commitment {
+    pub digest: ByteStream,
+    pub prefix_blocks: usize,
+}
+
+/// Extends `prefix` with a linking block and a path through `diamond` so
+/// that the result has `commitment`, made by that diamond, as its digest.
+/// Returns `None` unless `prefix` is exactly as many blocks as the commitment
+/// was made for. The linking block costs about `2^(b - k)` compressions for a
+/// `b`-bit hash; also returns `None` if none turns up in many times that.
+pub fn herd(
+    hash: &ToyHash,
+    diamond: &Diamond,
+    commitment: &Commitment,
+    prefix: &ByteStream,
+) -> Option<ByteStream> {
+    if prefix.len() != commitment.prefix_blocks * BLOCK_SIZE {
+        return None;
+    }
+    let leaves: HashMap<u32, usize> = diamond
+        .leaves()
+        .iter()
+        .enumerate()
+        .map(|(i, leaf)| (*leaf, i))
+        .collect();
+    let state = hash.iterate(hash.initial_state(), prefix);
+
+    let tries = 16u64 << (hash.bits() - diamond.depth());
+    for _ in 0..tries {
+        let link = ByteStream::random(BLOCK_SIZE);
+        if let Some(&leaf) = leaves.get(&hash.compress(state, &link.clone().into_bytes())) {
+            let mut message = prefix.clone();
+            message.append(&mut link.clone());
+            message.append(&mut diamond.path(leaf));
+            return Some(message);
+        }
+    }
+    None
+}
+
+/// The expected number of compressions to herd through a diamond of depth
+/// `k` for a `bits`-bit hash with this construction, as `(building the
+/// diamond, finding the link)`.
+///
+/// Each of the `2^k - 1` collisions between two states is a birthday search
+/// costing about `2^(bits / 2 + 1)` compressions, two per candidate block, and
+/// each linking candidate hits one of the `2^k` leaves with probability
+/// `2^(k - bits)`.
+pub fn expected_work(bits: usize, k: usize) -> (f64, f64) {
+    let diamond = ((1u64 << k) - 1) as f64 * 2f64.powf(bits as f64 / 2.0 + 1.0);
+    let link = 2f64.powi((bits - k) as i32);
+    (diamond, link)
+}
+
+/// Kelsey and Kohno's bound on the same work, as `(building the diamond,
+/// finding the link)`. They let every node of a level search for collisions
+/// with any other rather than a fixed partner, which brings the diamond down
+/// to about `2^((bits + k) / 2 + 2)` compressions; the link costs `2^(bits - k)`
+/// either way.
+pub fn kelsey_kohno_bound(bits: usize, k: usize) -> (f64, f64) {
+    let diamond = 2f64.powf((bits + k) as f64 / 2.0 + 2.0);
+    let link = 2f64.powi((bits - k) as i32);
+    (diamond, link)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_a_diamond() {
+        let hash = ToyHash::new(2);
+        let diamond = Diamond::generate(&hash, 4);
+        assert_eq!(diamond.depth(), 4);
+        assert_eq!(diamond.leaves().len(), 16);
+        for (i, leaf) in diamond.leaves().iter().enumerate() {
+            let path = diamond.path(i);
+            assert_eq!(path.len(), 4 * BLOCK_SIZE);
+            assert_eq!(hash.iterate(*leaf, &path), diamond.state);
+        }
+    }
+
+    #[test]
+    fn it_herds_a_chosen_prefix_to_a_commitment() {
+        let hash = ToyHash::new(3);
+        let diamond = Diamond::generate(&hash, 6);
+        let commitment = diamond.commitment(&hash, 2);
+
+        let prefix = ByteStream::from_ascii("Yankees 4, Red Sox 3, in 11 inn.").unwrap();
+        let message = herd(&hash, &diamond, &commitment, &prefix).unwrap();
+        assert!(message
+            .clone()
+            .into_bytes()
+            .starts_with(&prefix.into_bytes()));
+        assert_eq!(hash.digest(&message), commitment.digest);
+
+        let unaligned = ByteStream::from_ascii("Yankees win").unwrap();
+        assert_eq!(herd(&hash, &diamond, &commitment, &unaligned), None);
+        let short = ByteStream::from_ascii("Yankees win, 4-3").unwrap();
+        assert_eq!(herd(&hash, &diamond, &commitment, &short), None);
+    }
+
+    #[test]
+    fn it_stays_near_the_expected_work() {
+        let hash = ToyHash::new(2);
+        let k = 4;
+        let diamond = Diamond::generate(&hash, k);
+        let built = hash.calls() as f64;
+        let (diamond_work, link_work) = expected_work(hash.bits(), k);
+        assert!(built > diamond_work / 4.0 && built < 4.0 * diamond_work);
+
+        // Each link search is geometric, so average a few of them.
+        let herds = 8;
+        let commitment = diamond.commitment(&hash, 1);
+        let mut linking = 0;
+        for i in 0..herds {
+            let prefix = ByteStream::from(vec![b'A' + i; BLOCK_SIZE]);
+            let before = hash.calls();
+            herd(&hash, &diamond, &commitment, &prefix).unwrap();
+            // Less the compression of the one-block prefix.
+            linking += hash.calls() - before - 1;
+        }
+        let mean = linking as f64 / f64::from(herds);
+        assert!(mean > link_work / 8.0 && mean < 4.0 * link_work);
+    }
+
+    #[test]
+    fn it_computes_the_kelsey_kohno_bound() {
+        assert_eq!(kelsey_kohno_bound(24, 6), (131072.0, 262144.0));
+        // The fixed pairing is only competitive for tiny diamonds.
+        assert!(expected_work(24, 2).0 < 2.0 * kelsey_kohno_bound(24, 2).0);
+        assert!(expected_work(24, 12).0 > 10.0 * kelsey_kohno_bound(24, 12).0);
+    }
+}
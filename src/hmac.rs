@@ -0,0 +1,166 @@
+//!
+//! HMAC (RFC 2104) over any of the crate's hash functions.
+//!
+
+use byte_stream::ByteStream;
+use hash::Hash;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+pub struct Hmac<H: Hash> {
+    inner: H,
+    outer_key: ByteStream,
+}
+
+impl<H: Hash> Hmac<H> {
+    pub fn new(key: &ByteStream) -> Hmac<H> {
+        let mut key = if key.len() > H::BLOCK_SIZE {
+            H::digest(key)
+        } else {
+            key.clone()
+        };
+        while key.len() < H::BLOCK_SIZE {
+            key.push(0);
+        }
+
+        let mut inner_key = key.clone();
+        inner_key.byte_xor(IPAD);
+        let mut outer_key = key;
+        outer_key.byte_xor(OPAD);
+
+        let mut inner = H::new();
+        inner.update(&inner_key);
+        Hmac { inner, outer_key }
+    }
+
+    pub fn update(&mut self, bs: &ByteStream) {
+        self.inner.update(bs);
+    }
+
+    pub fn finalize(self) -> ByteStream {
+        let mut outer = H::new();
+        outer.update(&self.outer_key);
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+}
+
+pub fn hmac<H: Hash>(key: &ByteStream, message: &ByteStream) -> ByteStream {
+    let mut mac = Hmac::<H>::new(key);
+    mac.update(message);
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha1::Sha1;
+    use sha2::{Sha224, Sha256, Sha384, Sha512};
+
+    fn repeated(byte: u8, n: usize) -> ByteStream {
+        ByteStream::from(vec![byte; n])
+    }
+
+    fn ascii(s: &str) -> ByteStream {
+        ByteStream::from_ascii(s).unwrap()
+    }
+
+    #[test]
+    fn it_matches_rfc_2202_vectors() {
+        let cases = vec![
+            (
+                repeated(0x0b, 20),
+                ascii("Hi There"),
+                "b617318655057264e28bc0b6fb378c8ef146be00",
+            ),
+            (
+                ascii("Jefe"),
+                ascii("what do ya want for nothing?"),
+                "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            ),
+            (
+                repeated(0xaa, 20),
+                repeated(0xdd, 50),
+                "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+            ),
+            (
+                ByteStream::from_hex("0102030405060708090a0b0c0d0e0f10111213141516171819").unwrap(),
+                repeated(0xcd, 50),
+                "4c9007f4026250c6bc8414f9bf50c86c2d7235da",
+            ),
+            (
+                repeated(0xaa, 80),
+                ascii("Test Using Larger Than Block-Size Key - Hash Key First"),
+                "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            ),
+            (
+                repeated(0xaa, 80),
+                ascii("Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data"),
+                "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+            ),
+        ];
+        for (key, message, expected) in cases {
+            assert_eq!(hmac::<Sha1>(&key, &message).into_hex(), expected);
+        }
+    }
+
+    #[test]
+    fn it_matches_rfc_4231_vectors() {
+        let cases = vec![
+            (
+                repeated(0x0b, 20),
+                ascii("Hi There"),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                ascii("Jefe"),
+                ascii("what do ya want for nothing?"),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                repeated(0xaa, 20),
+                repeated(0xdd, 50),
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+            (
+                repeated(0xaa, 131),
+                ascii("Test Using Larger Than Block-Size Key - Hash Key First"),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+        for (key, message, expected) in cases {
+            assert_eq!(hmac::<Sha256>(&key, &message).into_hex(), expected);
+        }
+    }
+
+    #[test]
+    fn it_hashes_incrementally() {
+        let key = ascii("Jefe");
+        let mut mac = Hmac::<Sha256>::new(&key);
+        mac.update(&ascii("what do ya want "));
+        mac.update(&ascii("for nothing?"));
+        assert_eq!(
+            mac.finalize(),
+            hmac::<Sha256>(&key, &ascii("what do ya want for nothing?"))
+        );
+    }
+
+    #[test]
+    fn it_matches_rfc_4231_vectors_for_other_sizes() {
+        let key = ascii("Jefe");
+        let message = ascii("what do ya want for nothing?");
+        assert_eq!(
+            hmac::<Sha224>(&key, &message).into_hex(),
+            "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44"
+        );
+        assert_eq!(
+            hmac::<Sha384>(&key, &message).into_hex(),
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649"
+        );
+        assert_eq!(
+            hmac::<Sha512>(&key, &message).into_hex(),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+}
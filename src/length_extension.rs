@@ -0,0 +1,202 @@
+//!
+//! Length-extension forgeries against `H(secret || message)` MACs built on
+//! Merkle–Damgård hashes.
+//!
+
+use byte_stream::ByteStream;
+use hash::Hash;
+use md4::Md4;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// A Merkle–Damgård hash whose state can be recovered from a digest and
+/// resumed.
+pub trait MerkleDamgard: Hash {
+    type State;
+
+    fn state_from_digest(digest: &ByteStream) -> Self::State;
+    fn from_state(state: Self::State, processed_len: u64) -> Self;
+    fn padding(message_len: u64) -> ByteStream;
+}
+
+impl MerkleDamgard for Sha1 {
+    type State = [u32; 5];
+
+    fn state_from_digest(digest: &ByteStream) -> Self::State {
+        Sha1::state_from_digest(digest)
+    }
+
+    fn from_state(state: Self::State, processed_len: u64) -> Self {
+        Sha1::from_state(state, processed_len)
+    }
+
+    fn padding(message_len: u64) -> ByteStream {
+        Sha1::padding(message_len)
+    }
+}
+
+impl MerkleDamgard for Md4 {
+    type State = [u32; 4];
+
+    fn state_from_digest(digest: &ByteStream) -> Self::State {
+        Md4::state_from_digest(digest)
+    }
+
+    fn from_state(state: Self::State, processed_len: u64) -> Self {
+        Md4::from_state(state, processed_len)
+    }
+
+    fn padding(message_len: u64) -> ByteStream {
+        Md4::padding(message_len)
+    }
+}
+
+impl MerkleDamgard for Sha256 {
+    type State = [u32; 8];
+
+    fn state_from_digest(digest: &ByteStream) -> Self::State {
+        Sha256::state_from_digest(digest)
+    }
+
+    fn from_state(state: Self::State, processed_len: u64) -> Self {
+        Sha256::from_state(state, processed_len)
+    }
+
+    fn padding(message_len: u64) -> ByteStream {
+        Sha256::padding(message_len)
+    }
+}
+
+impl MerkleDamgard for Sha512 {
+    type State = [u64; 8];
+
+    fn state_from_digest(digest: &ByteStream) -> Self::State {
+        Sha512::state_from_digest(digest)
+    }
+
+    fn from_state(state: Self::State, processed_len: u64) -> Self {
+        Sha512::from_state(state, processed_len)
+    }
+
+    fn padding(message_len: u64) -> ByteStream {
+        Sha512::padding(message_len)
+    }
+}
+
+/// A forged message and the MAC it should carry, assuming the secret is
+/// `secret_len` bytes long.
+pub struct Forgery {
+    pub secret_len: usize,
+    pub message: ByteStream,
+    pub mac: ByteStream,
+}
+
+/// Forges `message || glue padding || extension` for each candidate secret
+/// length, given the MAC of `message`.
+pub fn forge<H: MerkleDamgard>(
+    message: &ByteStream,
+    mac: &ByteStream,
+    extension: &ByteStream,
+    secret_lens: Range<usize>,
+) -> Vec<Forgery> {
+    let mut forgeries = Vec::new();
+    for secret_len in secret_lens {
+        let original_len = (secret_len + message.len()) as u64;
+        let mut glue = H::padding(original_len);
+
+        let mut hasher = H::from_state(H::state_from_digest(mac), original_len + glue.len() as u64);
+        hasher.update(extension);
+
+        let mut forged = message.clone();
+        forged.append(&mut glue);
+        forged.append(&mut extension.clone());
+
+        forgeries.push(Forgery {
+            secret_len,
+            message: forged,
+            mac: hasher.finalize(),
+        });
+    }
+    forgeries
+}
+
+/// A server that signs and verifies messages with a secret-prefix MAC.
+pub struct Oracle<H: MerkleDamgard> {
+    key: ByteStream,
+    hash: PhantomData<H>,
+}
+
+impl<H: MerkleDamgard> Oracle<H> {
+    pub fn new(key: ByteStream) -> Oracle<H> {
+        Oracle {
+            key,
+            hash: PhantomData,
+        }
+    }
+
+    pub fn sign(&self, message: &ByteStream) -> ByteStream {
+        let mut hasher = H::new();
+        hasher.update(&self.key);
+        hasher.update(message);
+        hasher.finalize()
+    }
+
+    pub fn verify(&self, message: &ByteStream, mac: &ByteStream) -> bool {
+        self.sign(message).ct_eq(mac)
+    }
+}
+
+/// Tries each forgery against the oracle and returns the first it accepts.
+pub fn attack<H: MerkleDamgard>(
+    oracle: &Oracle<H>,
+    message: &ByteStream,
+    mac: &ByteStream,
+    extension: &ByteStream,
+    secret_lens: Range<usize>,
+) -> Option<Forgery> {
+    forge::<H>(message, mac, extension, secret_lens)
+        .into_iter()
+        .find(|f| oracle.verify(&f.message, &f.mac))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn it_extends<H: MerkleDamgard>() {
+        let oracle = Oracle::<H>::new(ByteStream::from_ascii("purple rain").unwrap());
+        let message = ByteStream::from_ascii(
+            "comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon",
+        )
+        .unwrap();
+        let mac = oracle.sign(&message);
+        let extension = ByteStream::from_ascii(";admin=true").unwrap();
+
+        let forgery = attack(&oracle, &message, &mac, &extension, 0..32).unwrap();
+        assert_eq!(forgery.secret_len, 11);
+        assert!(oracle.verify(&forgery.message, &forgery.mac));
+        assert!(forgery.message.into_bytes().ends_with(b";admin=true"));
+    }
+
+    #[test]
+    fn it_extends_sha1() {
+        it_extends::<Sha1>();
+    }
+
+    #[test]
+    fn it_extends_md4() {
+        it_extends::<Md4>();
+    }
+
+    #[test]
+    fn it_extends_sha256() {
+        it_extends::<Sha256>();
+    }
+
+    #[test]
+    fn it_extends_sha512() {
+        it_extends::<Sha512>();
+    }
+}
@@ -4,4 +4,30 @@
 
 #![feature(str_escape)]
 
+pub mod bignum;
+pub mod bleichenbacher;
 pub mod byte_stream;
+pub mod cbc_mac;
+pub mod compression_oracle;
+pub mod deflate;
+pub mod dh;
+pub mod dsa;
+pub mod expandable_message;
+pub mod hash;
+pub mod herding;
+pub mod hmac;
+pub mod length_extension;
+pub mod md4;
+pub mod mitm;
+pub mod mt19937;
+pub mod multicollision;
+pub mod rsa;
+pub mod rsa_broadcast;
+pub mod rsa_parity;
+pub mod rsa_signature_forgery;
+pub mod rsa_unpadded;
+pub mod sha1;
+pub mod sha2;
+pub mod srp;
+pub mod timing_leak;
+pub mod toy_hash;
@@ -0,0 +1,273 @@
+//!
+//! MD4 (RFC 1320), with the same state-injection API as `Sha1`.
+//!
+
+use byte_stream::ByteStream;
+use hash::Hash;
+
+const BLOCK_SIZE: usize = 64;
+
+const INITIAL_STATE: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+#[derive(Clone)]
+pub struct Md4 {
+    state: [u32; 4],
+    buffer: Vec<u8>,
+    len: u64,
+}
+
+impl Md4 {
+    pub fn new() -> Md4 {
+        Self::from_state(INITIAL_STATE, 0)
+    }
+
+    /// Resumes hashing from the given registers, as though `processed_len`
+    /// bytes (a multiple of the block size, including any padding) had
+    /// already been consumed.
+    pub fn from_state(state: [u32; 4], processed_len: u64) -> Md4 {
+        assert!(processed_len.is_multiple_of(BLOCK_SIZE as u64));
+        Md4 {
+            state,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            len: processed_len,
+        }
+    }
+
+    /// Recovers the registers that produced `digest`.
+    pub fn state_from_digest(digest: &ByteStream) -> [u32; 4] {
+        assert!(digest.len() == 16);
+        let mut state = [0; 4];
+        for (s, chunk) in state.iter_mut().zip(digest.iter_blocks(4)) {
+            *s = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        state
+    }
+
+    pub fn state(&self) -> [u32; 4] {
+        self.state
+    }
+
+    /// The number of bytes hashed into `state`, which together with it is
+    /// enough to resume with `from_state`. Panics if the input so far doesn't
+    /// fill whole blocks, since the buffered bytes would be lost.
+    pub fn processed_len(&self) -> u64 {
+        assert!(self.buffer.is_empty(), "not on a block boundary");
+        self.len
+    }
+
+    /// The padding that MD4 appends to a message of `message_len` bytes.
+    pub fn padding(message_len: u64) -> ByteStream {
+        let mut padding = ByteStream::from_bytes(&[0x80]);
+        while (message_len as usize + padding.len()) % BLOCK_SIZE != BLOCK_SIZE - 8 {
+            padding.push(0);
+        }
+        for b in (message_len.wrapping_mul(8)).to_le_bytes().iter() {
+            padding.push(*b);
+        }
+        padding
+    }
+
+    pub fn update(&mut self, bs: &ByteStream) {
+        for b in bs.iter() {
+            self.buffer.push(*b);
+            self.len += 1;
+            if self.buffer.len() == BLOCK_SIZE {
+                let block = self.buffer.split_off(0);
+                self.process_block(&block);
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> ByteStream {
+        let padding = Self::padding(self.len);
+        self.update(&padding);
+        assert!(self.buffer.is_empty());
+
+        let mut digest = ByteStream::new();
+        for word in self.state.iter() {
+            for b in word.to_le_bytes().iter() {
+                digest.push(*b);
+            }
+        }
+        digest
+    }
+
+    fn process_block(&mut self, block: &[u8]) {
+        let mut x = [0u32; 16];
+        for (i, chunk) in block.chunks(4).enumerate() {
+            x[i] = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = self.state;
+
+        let f = |x: u32, y: u32, z: u32| (x & y) | (!x & z);
+        let g = |x: u32, y: u32, z: u32| (x & y) | (x & z) | (y & z);
+        let h = |x: u32, y: u32, z: u32| x ^ y ^ z;
+
+        for &i in &[0, 4, 8, 12] {
+            a = a.wrapping_add(f(b, c, d)).wrapping_add(x[i]).rotate_left(3);
+            d = d
+                .wrapping_add(f(a, b, c))
+                .wrapping_add(x[i + 1])
+                .rotate_left(7);
+            c = c
+                .wrapping_add(f(d, a, b))
+                .wrapping_add(x[i + 2])
+                .rotate_left(11);
+            b = b
+                .wrapping_add(f(c, d, a))
+                .wrapping_add(x[i + 3])
+                .rotate_left(19);
+        }
+
+        for &i in &[0, 1, 2, 3] {
+            a = a
+                .wrapping_add(g(b, c, d))
+                .wrapping_add(x[i])
+                .wrapping_add(0x5a82_7999)
+                .rotate_left(3);
+            d = d
+                .wrapping_add(g(a, b, c))
+                .wrapping_add(x[i + 4])
+                .wrapping_add(0x5a82_7999)
+                .rotate_left(5);
+            c = c
+                .wrapping_add(g(d, a, b))
+                .wrapping_add(x[i + 8])
+                .wrapping_add(0x5a82_7999)
+                .rotate_left(9);
+            b = b
+                .wrapping_add(g(c, d, a))
+                .wrapping_add(x[i + 12])
+                .wrapping_add(0x5a82_7999)
+                .rotate_left(13);
+        }
+
+        for &i in &[0, 2, 1, 3] {
+            a = a
+                .wrapping_add(h(b, c, d))
+                .wrapping_add(x[i])
+                .wrapping_add(0x6ed9_eba1)
+                .rotate_left(3);
+            d = d
+                .wrapping_add(h(a, b, c))
+                .wrapping_add(x[i + 8])
+                .wrapping_add(0x6ed9_eba1)
+                .rotate_left(9);
+            c = c
+                .wrapping_add(h(d, a, b))
+                .wrapping_add(x[i + 4])
+                .wrapping_add(0x6ed9_eba1)
+                .rotate_left(11);
+            b = b
+                .wrapping_add(h(c, d, a))
+                .wrapping_add(x[i + 12])
+                .wrapping_add(0x6ed9_eba1)
+                .rotate_left(15);
+        }
+
+        self.state[0] = self.state[0].wrapping_add(a);
+        self.state[1] = self.state[1].wrapping_add(b);
+        self.state[2] = self.state[2].wrapping_add(c);
+        self.state[3] = self.state[3].wrapping_add(d);
+    }
+}
+
+impl Default for Md4 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Md4 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 16;
+
+    fn new() -> Self {
+        Md4::new()
+    }
+
+    fn update(&mut self, bs: &ByteStream) {
+        Md4::update(self, bs)
+    }
+
+    fn finalize(self) -> ByteStream {
+        Md4::finalize(self)
+    }
+}
+
+pub fn md4(bs: &ByteStream) -> ByteStream {
+    Md4::digest(bs)
+}
+
+/// The naive `MD4(key || message)` MAC.
+pub fn secret_prefix_mac(key: &ByteStream, message: &ByteStream) -> ByteStream {
+    let mut hasher = Md4::new();
+    hasher.update(key);
+    hasher.update(message);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_ascii(s: &str) -> String {
+        md4(&ByteStream::from_ascii(s).unwrap()).into_hex()
+    }
+
+    #[test]
+    fn it_hashes_rfc_1320_vectors() {
+        assert_eq!(hash_ascii(""), "31d6cfe0d16ae931b73c59d7e0c089c0");
+        assert_eq!(hash_ascii("a"), "bde52cb31de33e46245e05fbdbd6fb24");
+        assert_eq!(hash_ascii("abc"), "a448017aaf21d8525fc10ae87aa6729d");
+        assert_eq!(
+            hash_ascii("message digest"),
+            "d9130a8164549fe818874806e1c7014b"
+        );
+        assert_eq!(
+            hash_ascii("abcdefghijklmnopqrstuvwxyz"),
+            "d79e1c308aa5bbcdeea8ed63df412da9"
+        );
+        assert_eq!(
+            hash_ascii("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"),
+            "043f8582f241db351ce627e153e7f0e4"
+        );
+        assert_eq!(
+            hash_ascii(
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+            ),
+            "e33b4ddc9c38f2199c3e7b164fcc0536"
+        );
+    }
+
+    #[test]
+    fn it_resumes_from_a_digest() {
+        let mut message = ByteStream::from_ascii("message digest").unwrap();
+        let digest = md4(&message);
+        message.append(&mut Md4::padding(14));
+        let mut suffix = ByteStream::from_ascii(";admin=true").unwrap();
+
+        let mut hasher = Md4::from_state(Md4::state_from_digest(&digest), message.len() as u64);
+        hasher.update(&suffix);
+
+        message.append(&mut suffix);
+        assert_eq!(hasher.finalize(), md4(&message));
+    }
+
+    #[test]
+    fn it_exports_state_on_block_boundaries() {
+        let mut hasher = Md4::new();
+        hasher.update(&ByteStream::from(vec![b'a'; BLOCK_SIZE]));
+        assert_eq!(hasher.processed_len(), BLOCK_SIZE as u64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_refuses_to_export_state_mid_block() {
+        let mut hasher = Md4::new();
+        hasher.update(&ByteStream::from(vec![b'a'; BLOCK_SIZE]));
+        hasher.update(&ByteStream::from_ascii("abc").unwrap());
+        hasher.processed_len();
+    }
+}
@@ -0,0 +1,506 @@
+//!
+//! An in-process model of two-party protocols with an attacker sitting on the
+//! channel between them, plus the Diffie-Hellman echo protocol and the
+//! classic attacks against it.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use dh::{derive_key, Group, Keypair};
+use sha1::Sha1;
+use std::collections::VecDeque;
+
+const BLOCK_SIZE: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// A proposed group, or the acceptance of one when sent back.
+    Group {
+        p: BigUint,
+        g: BigUint,
+    },
+    /// A group and the sender's public key in one go.
+    Params {
+        p: BigUint,
+        g: BigUint,
+        public: BigUint,
+    },
+    PublicKey(BigUint),
+    /// An AES-128-CBC ciphertext and the IV it was encrypted under.
+    Encrypted {
+        ciphertext: ByteStream,
+        iv: ByteStream,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    ToResponder,
+    ToInitiator,
+}
+
+/// Sees every message put on a channel and decides what is delivered in its
+/// place: nothing (drop), one message (forward or rewrite), or several
+/// (replay).
+pub trait Interceptor {
+    fn intercept(&mut self, direction: Direction, message: Message) -> Vec<Message>;
+}
+
+impl<F> Interceptor for F
+where
+    F: FnMut(Direction, Message) -> Vec<Message>,
+{
+    fn intercept(&mut self, direction: Direction, message: Message) -> Vec<Message> {
+        self(direction, message)
+    }
+}
+
+/// Delivers every message untouched.
+pub struct Passthrough;
+
+impl Interceptor for Passthrough {
+    fn intercept(&mut self, _direction: Direction, message: Message) -> Vec<Message> {
+        vec![message]
+    }
+}
+
+pub struct Channel<I: Interceptor> {
+    interceptor: I,
+    to_responder: VecDeque<Message>,
+    to_initiator: VecDeque<Message>,
+}
+
+impl<I: Interceptor> Channel<I> {
+    pub fn new(interceptor: I) -> Channel<I> {
+        Channel {
+            interceptor,
+            to_responder: VecDeque::new(),
+            to_initiator: VecDeque::new(),
+        }
+    }
+
+    pub fn send(&mut self, direction: Direction, message: Message) {
+        let delivered = self.interceptor.intercept(direction, message);
+        let queue = match direction {
+            Direction::ToResponder => &mut self.to_responder,
+            Direction::ToInitiator => &mut self.to_initiator,
+        };
+        queue.extend(delivered);
+    }
+
+    pub fn recv(&mut self, direction: Direction) -> Option<Message> {
+        match direction {
+            Direction::ToResponder => self.to_responder.pop_front(),
+            Direction::ToInitiator => self.to_initiator.pop_front(),
+        }
+    }
+
+    pub fn interceptor(&self) -> &I {
+        &self.interceptor
+    }
+}
+
+/// One side of a protocol, driven entirely by the messages it receives.
+pub trait Party {
+    fn start(&mut self) -> Vec<Message> {
+        Vec::new()
+    }
+
+    fn receive(&mut self, message: Message) -> Vec<Message>;
+}
+
+/// Runs a protocol until no messages are in flight or `max_messages` have
+/// been delivered, returning how many were delivered.
+pub fn run<A: Party, B: Party, I: Interceptor>(
+    initiator: &mut A,
+    responder: &mut B,
+    channel: &mut Channel<I>,
+    max_messages: usize,
+) -> usize {
+    for message in initiator.start() {
+        channel.send(Direction::ToResponder, message);
+    }
+
+    let mut delivered = 0;
+    while delivered < max_messages {
+        if let Some(message) = channel.recv(Direction::ToResponder) {
+            for reply in responder.receive(message) {
+                channel.send(Direction::ToInitiator, reply);
+            }
+        } else if let Some(message) = channel.recv(Direction::ToInitiator) {
+            for reply in initiator.receive(message) {
+                channel.send(Direction::ToResponder, reply);
+            }
+        } else {
+            break;
+        }
+        delivered += 1;
+    }
+    delivered
+}
+
+pub fn encrypt(key: &ByteStream, plaintext: &ByteStream) -> Message {
+    let iv = ByteStream::random(BLOCK_SIZE);
+    let mut ciphertext = plaintext.clone();
+    ciphertext.pkcs7_pad(BLOCK_SIZE);
+    ciphertext.encrypt_aes_128_cbc(key.clone(), &iv);
+    Message::Encrypted { ciphertext, iv }
+}
+
+/// Decrypts a ciphertext, returning `None` if it isn't properly padded.
+pub fn decrypt(key: &ByteStream, ciphertext: &ByteStream, iv: &ByteStream) -> Option<ByteStream> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+        return None;
+    }
+    let mut plaintext = ciphertext.clone();
+    plaintext.decrypt_aes_128_cbc(key.clone(), iv);
+    plaintext.pkcs7_unpad().ok().map(|_| plaintext)
+}
+
+fn session_key(secret: &BigUint) -> ByteStream {
+    derive_key::<Sha1>(secret)
+}
+
+/// Agrees on a key with the responder, sends it one encrypted message and
+/// collects whatever comes back.
+pub struct EchoClient {
+    group: Group,
+    negotiate: bool,
+    keypair: Option<Keypair>,
+    key: Option<ByteStream>,
+    message: ByteStream,
+    pub echoes: Vec<ByteStream>,
+}
+
+impl EchoClient {
+    /// A client that sends its group and public key in the first message.
+    pub fn new(group: Group, message: ByteStream) -> EchoClient {
+        EchoClient {
+            group,
+            negotiate: false,
+            keypair: None,
+            key: None,
+            message,
+            echoes: Vec::new(),
+        }
+    }
+
+    /// A client that waits for the responder to accept its group before
+    /// sending a public key.
+    pub fn negotiating(group: Group, message: ByteStream) -> EchoClient {
+        EchoClient {
+            negotiate: true,
+            ..EchoClient::new(group, message)
+        }
+    }
+}
+
+impl Party for EchoClient {
+    fn start(&mut self) -> Vec<Message> {
+        if self.negotiate {
+            return vec![Message::Group {
+                p: self.group.p.clone(),
+                g: self.group.g.clone(),
+            }];
+        }
+        let keypair = self.group.generate_keypair();
+        let params = Message::Params {
+            p: self.group.p.clone(),
+            g: self.group.g.clone(),
+            public: keypair.public.clone(),
+        };
+        self.keypair = Some(keypair);
+        vec![params]
+    }
+
+    fn receive(&mut self, message: Message) -> Vec<Message> {
+        match message {
+            Message::Group { p, g } => {
+                self.group = Group::new(p, g);
+                let keypair = self.group.generate_keypair();
+                let public = keypair.public.clone();
+                self.keypair = Some(keypair);
+                vec![Message::PublicKey(public)]
+            }
+            Message::PublicKey(public) => match self.keypair {
+                Some(ref keypair) => {
+                    let key = session_key(&keypair.shared_secret(&self.group, &public));
+                    let reply = encrypt(&key, &self.message);
+                    self.key = Some(key);
+                    vec![reply]
+                }
+                None => Vec::new(),
+            },
+            Message::Encrypted { ciphertext, iv } => {
+                if let Some(plaintext) =
+                    self.key.as_ref().and_then(|k| decrypt(k, &ciphertext, &iv))
+                {
+                    self.echoes.push(plaintext);
+                }
+                Vec::new()
+            }
+            Message::Params { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Accepts whatever group it is offered and echoes every message it can
+/// decrypt back under a fresh IV.
+pub struct EchoServer {
+    group: Option<Group>,
+    key: Option<ByteStream>,
+    pub received: Vec<ByteStream>,
+}
+
+impl EchoServer {
+    pub fn new() -> EchoServer {
+        EchoServer {
+            group: None,
+            key: None,
+            received: Vec::new(),
+        }
+    }
+
+    fn agree(&mut self, group: Group, public: &BigUint) -> Message {
+        let keypair = group.generate_keypair();
+        self.key = Some(session_key(&keypair.shared_secret(&group, public)));
+        self.group = Some(group);
+        Message::PublicKey(keypair.public)
+    }
+}
+
+impl Default for EchoServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Party for EchoServer {
+    fn receive(&mut self, message: Message) -> Vec<Message> {
+        match message {
+            Message::Params { p, g, public } => vec![self.agree(Group::new(p, g), &public)],
+            Message::Group { p, g } => {
+                self.group = Some(Group::new(p.clone(), g.clone()));
+                vec![Message::Group { p, g }]
+            }
+            Message::PublicKey(public) => match self.group.clone() {
+                Some(group) => vec![self.agree(group, &public)],
+                None => Vec::new(),
+            },
+            Message::Encrypted { ciphertext, iv } => {
+                let key = match self.key {
+                    Some(ref key) => key.clone(),
+                    None => return Vec::new(),
+                };
+                match decrypt(&key, &ciphertext, &iv) {
+                    Some(plaintext) => {
+                        let reply = encrypt(&key, &plaintext);
+                        self.received.push(plaintext);
+                        vec![reply]
+                    }
+                    None => Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+/// Replaces both public keys with `p`, forcing the shared secret to zero,
+/// and reads every encrypted message that passes by.
+pub struct KeyFixing {
+    p: Option<BigUint>,
+    pub recovered: Vec<ByteStream>,
+}
+
+impl KeyFixing {
+    pub fn new() -> KeyFixing {
+        KeyFixing {
+            p: None,
+            recovered: Vec::new(),
+        }
+    }
+}
+
+impl Default for KeyFixing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interceptor for KeyFixing {
+    fn intercept(&mut self, _direction: Direction, message: Message) -> Vec<Message> {
+        let message = match (message, self.p.clone()) {
+            (Message::Params { p, g, .. }, _) => {
+                self.p = Some(p.clone());
+                Message::Params {
+                    p: p.clone(),
+                    g,
+                    public: p,
+                }
+            }
+            (Message::PublicKey(_), Some(p)) => Message::PublicKey(p),
+            (Message::Encrypted { ciphertext, iv }, _) => {
+                if let Some(plaintext) = decrypt(&session_key(&BigUint::zero()), &ciphertext, &iv) {
+                    self.recovered.push(plaintext);
+                }
+                Message::Encrypted { ciphertext, iv }
+            }
+            (message, _) => message,
+        };
+        vec![message]
+    }
+}
+
+/// The generators an attacker can substitute during group negotiation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaliciousGenerator {
+    One,
+    P,
+    PMinusOne,
+}
+
+/// Rewrites the negotiated generator so that the shared secret can only take
+/// one or two values, then reads every encrypted message that passes by.
+pub struct MaliciousG {
+    generator: MaliciousGenerator,
+    p: Option<BigUint>,
+    pub recovered: Vec<ByteStream>,
+}
+
+impl MaliciousG {
+    pub fn new(generator: MaliciousGenerator) -> MaliciousG {
+        MaliciousG {
+            generator,
+            p: None,
+            recovered: Vec::new(),
+        }
+    }
+
+    /// The shared secrets that can result from the substituted generator.
+    fn candidate_secrets(&self, p: &BigUint) -> Vec<BigUint> {
+        match self.generator {
+            MaliciousGenerator::One => vec![BigUint::one()],
+            MaliciousGenerator::P => vec![BigUint::zero()],
+            MaliciousGenerator::PMinusOne => vec![BigUint::one(), p - BigUint::one()],
+        }
+    }
+}
+
+impl Interceptor for MaliciousG {
+    fn intercept(&mut self, direction: Direction, message: Message) -> Vec<Message> {
+        let message = match message {
+            Message::Group { p, .. } if direction == Direction::ToResponder => {
+                let g = match self.generator {
+                    MaliciousGenerator::One => BigUint::one(),
+                    MaliciousGenerator::P => p.clone(),
+                    MaliciousGenerator::PMinusOne => &p - BigUint::one(),
+                };
+                self.p = Some(p.clone());
+                Message::Group { p, g }
+            }
+            Message::Encrypted { ciphertext, iv } => {
+                if let Some(ref p) = self.p {
+                    let recovered = self
+                        .candidate_secrets(p)
+                        .iter()
+                        .filter_map(|s| decrypt(&session_key(s), &ciphertext, &iv))
+                        .next();
+                    if let Some(plaintext) = recovered {
+                        self.recovered.push(plaintext);
+                    }
+                }
+                Message::Encrypted { ciphertext, iv }
+            }
+            message => message,
+        };
+        vec![message]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> ByteStream {
+        ByteStream::from_ascii("the eagle has landed").unwrap()
+    }
+
+    fn group() -> Group {
+        Group::modp(1536).unwrap()
+    }
+
+    #[test]
+    fn it_echoes_without_an_attacker() {
+        let mut client = EchoClient::new(group(), message());
+        let mut server = EchoServer::new();
+        let mut channel = Channel::new(Passthrough);
+        run(&mut client, &mut server, &mut channel, 100);
+
+        assert_eq!(server.received, vec![message()]);
+        assert_eq!(client.echoes, vec![message()]);
+    }
+
+    #[test]
+    fn it_reads_messages_after_fixing_the_key() {
+        let mut client = EchoClient::new(group(), message());
+        let mut server = EchoServer::new();
+        let mut channel = Channel::new(KeyFixing::new());
+        run(&mut client, &mut server, &mut channel, 100);
+
+        assert_eq!(client.echoes, vec![message()]);
+        assert_eq!(channel.interceptor().recovered, vec![message(), message()]);
+    }
+
+    #[test]
+    fn it_reads_messages_after_substituting_g() {
+        for &generator in &[
+            MaliciousGenerator::One,
+            MaliciousGenerator::P,
+            MaliciousGenerator::PMinusOne,
+        ] {
+            let mut client = EchoClient::negotiating(group(), message());
+            let mut server = EchoServer::new();
+            let mut channel = Channel::new(MaliciousG::new(generator));
+            run(&mut client, &mut server, &mut channel, 100);
+
+            assert_eq!(client.echoes, vec![message()], "{:?}", generator);
+            assert_eq!(
+                channel.interceptor().recovered,
+                vec![message(), message()],
+                "{:?}",
+                generator
+            );
+        }
+    }
+
+    #[test]
+    fn it_drops_messages() {
+        let mut client = EchoClient::new(group(), message());
+        let mut server = EchoServer::new();
+        let mut channel = Channel::new(|direction, message| match (direction, message) {
+            (Direction::ToInitiator, Message::Encrypted { .. }) => Vec::new(),
+            (_, message) => vec![message],
+        });
+        run(&mut client, &mut server, &mut channel, 100);
+
+        assert_eq!(server.received, vec![message()]);
+        assert!(client.echoes.is_empty());
+    }
+
+    #[test]
+    fn it_replays_messages() {
+        let mut client = EchoClient::new(group(), message());
+        let mut server = EchoServer::new();
+        let mut channel = Channel::new(|direction, message| match (direction, message) {
+            (Direction::ToResponder, Message::Encrypted { ciphertext, iv }) => {
+                let replayed = Message::Encrypted { ciphertext, iv };
+                vec![replayed.clone(), replayed]
+            }
+            (_, message) => vec![message],
+        });
+        run(&mut client, &mut server, &mut channel, 100);
+
+        assert_eq!(server.received, vec![message(), message()]);
+        assert_eq!(client.echoes, vec![message(), message()]);
+    }
+}
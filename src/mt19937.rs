@@ -0,0 +1,375 @@
+//!
+//! The MT19937 Mersenne Twister, in its 32-bit and 64-bit variants, and
+//! attacks against it.
+//!
+
+use byte_stream::ByteStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908_b0df;
+const UPPER_MASK: u32 = 0x8000_0000;
+const LOWER_MASK: u32 = 0x7fff_ffff;
+const F: u32 = 1_812_433_253;
+
+const N_64: usize = 312;
+const M_64: usize = 156;
+const MATRIX_A_64: u64 = 0xb502_6f5a_a966_19e9;
+const UPPER_MASK_64: u64 = 0xffff_ffff_8000_0000;
+const LOWER_MASK_64: u64 = 0x7fff_ffff;
+const F_64: u64 = 6_364_136_223_846_793_005;
+
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Mt19937 {
+    pub fn new(seed: u32) -> Mt19937 {
+        let mut mt = Mt19937 {
+            state: [0; N],
+            index: N,
+        };
+        mt.seed(seed);
+        mt
+    }
+
+    /// Builds a generator directly from its internal state. The next call to
+    /// `extract` will twist the state before producing any output.
+    pub fn from_state(state: [u32; N]) -> Mt19937 {
+        Mt19937 { state, index: N }
+    }
+
+    pub fn seed(&mut self, seed: u32) {
+        self.state[0] = seed;
+        for i in 1..N {
+            let prev = self.state[i - 1];
+            self.state[i] = F.wrapping_mul(prev ^ (prev >> 30)).wrapping_add(i as u32);
+        }
+        self.index = N;
+    }
+
+    pub fn extract(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+        let y = self.state[self.index];
+        self.index += 1;
+        temper(y)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            let x = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let mut x_a = x >> 1;
+            if x & 1 != 0 {
+                x_a ^= MATRIX_A;
+            }
+            self.state[i] = self.state[(i + M) % N] ^ x_a;
+        }
+        self.index = 0;
+    }
+}
+
+pub struct Mt19937_64 {
+    state: [u64; N_64],
+    index: usize,
+}
+
+impl Mt19937_64 {
+    pub fn new(seed: u64) -> Mt19937_64 {
+        let mut mt = Mt19937_64 {
+            state: [0; N_64],
+            index: N_64,
+        };
+        mt.seed(seed);
+        mt
+    }
+
+    pub fn from_state(state: [u64; N_64]) -> Mt19937_64 {
+        Mt19937_64 { state, index: N_64 }
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.state[0] = seed;
+        for i in 1..N_64 {
+            let prev = self.state[i - 1];
+            self.state[i] = F_64
+                .wrapping_mul(prev ^ (prev >> 62))
+                .wrapping_add(i as u64);
+        }
+        self.index = N_64;
+    }
+
+    pub fn extract(&mut self) -> u64 {
+        if self.index >= N_64 {
+            self.twist();
+        }
+        let y = self.state[self.index];
+        self.index += 1;
+        temper_64(y)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N_64 {
+            let x = (self.state[i] & UPPER_MASK_64) | (self.state[(i + 1) % N_64] & LOWER_MASK_64);
+            let mut x_a = x >> 1;
+            if x & 1 != 0 {
+                x_a ^= MATRIX_A_64;
+            }
+            self.state[i] = self.state[(i + M_64) % N_64] ^ x_a;
+        }
+        self.index = 0;
+    }
+}
+
+pub fn temper(y: u32) -> u32 {
+    let mut y = y;
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c_5680;
+    y ^= (y << 15) & 0xefc6_0000;
+    y ^= y >> 18;
+    y
+}
+
+pub fn untemper(y: u32) -> u32 {
+    let mut y = y;
+    y = undo_right_shift_xor(y as u64, 18, 0xffff_ffff, 32) as u32;
+    y = undo_left_shift_xor(y as u64, 15, 0xefc6_0000, 32) as u32;
+    y = undo_left_shift_xor(y as u64, 7, 0x9d2c_5680, 32) as u32;
+    y = undo_right_shift_xor(y as u64, 11, 0xffff_ffff, 32) as u32;
+    y
+}
+
+pub fn temper_64(y: u64) -> u64 {
+    let mut y = y;
+    y ^= (y >> 29) & 0x5555_5555_5555_5555;
+    y ^= (y << 17) & 0x71d6_7fff_eda6_0000;
+    y ^= (y << 37) & 0xfff7_eee0_0000_0000;
+    y ^= y >> 43;
+    y
+}
+
+pub fn untemper_64(y: u64) -> u64 {
+    let mut y = y;
+    y = undo_right_shift_xor(y, 43, 0xffff_ffff_ffff_ffff, 64);
+    y = undo_left_shift_xor(y, 37, 0xfff7_eee0_0000_0000, 64);
+    y = undo_left_shift_xor(y, 17, 0x71d6_7fff_eda6_0000, 64);
+    y = undo_right_shift_xor(y, 29, 0x5555_5555_5555_5555, 64);
+    y
+}
+
+/// Inverts `y ^= (y >> shift) & mask` on a `width`-bit word by recovering the
+/// result `shift` bits at a time, starting from the most significant end.
+fn undo_right_shift_xor(y: u64, shift: u32, mask: u64, width: u32) -> u64 {
+    let mut x = y;
+    let mut recovered = shift;
+    while recovered < width {
+        x = y ^ ((x >> shift) & mask);
+        recovered += shift;
+    }
+    x
+}
+
+/// Inverts `y ^= (y << shift) & mask` on a `width`-bit word by recovering the
+/// result `shift` bits at a time, starting from the least significant end.
+fn undo_left_shift_xor(y: u64, shift: u32, mask: u64, width: u32) -> u64 {
+    let width_mask = if width == 64 { !0 } else { (1u64 << width) - 1 };
+    let mut x = y;
+    let mut recovered = shift;
+    while recovered < width {
+        x = (y ^ ((x << shift) & mask)) & width_mask;
+        recovered += shift;
+    }
+    x
+}
+
+/// Reconstructs a generator from 624 consecutive outputs. The returned clone
+/// will produce the same values as the original from that point on.
+pub fn clone_from_outputs(outputs: &[u32]) -> Option<Mt19937> {
+    if outputs.len() < N {
+        return None;
+    }
+    let mut state = [0; N];
+    for (s, &o) in state.iter_mut().zip(outputs.iter()) {
+        *s = untemper(o);
+    }
+    Some(Mt19937::from_state(state))
+}
+
+/// Reconstructs a 64-bit generator from 312 consecutive outputs.
+pub fn clone_from_outputs_64(outputs: &[u64]) -> Option<Mt19937_64> {
+    if outputs.len() < N_64 {
+        return None;
+    }
+    let mut state = [0; N_64];
+    for (s, &o) in state.iter_mut().zip(outputs.iter()) {
+        *s = untemper_64(o);
+    }
+    Some(Mt19937_64::from_state(state))
+}
+
+pub fn unix_timestamp() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before the unix epoch")
+        .as_secs() as u32
+}
+
+/// Searches the `window` seconds up to and including `now` for the seed whose
+/// first output is `output`.
+pub fn crack_time_seed(output: u32, now: u32, window: u32) -> Option<u32> {
+    for delta in 0..=window {
+        let seed = now.wrapping_sub(delta);
+        if Mt19937::new(seed).extract() == output {
+            return Some(seed);
+        }
+    }
+    None
+}
+
+/// Produces `len` bytes of keystream by splitting each output of a generator
+/// seeded with `seed` into four big-endian bytes.
+pub fn keystream(seed: u32, len: usize) -> ByteStream {
+    let mut mt = Mt19937::new(seed);
+    let mut ks = ByteStream::new();
+    while ks.len() < len {
+        let word = mt.extract();
+        for shift in &[24, 16, 8, 0] {
+            if ks.len() < len {
+                ks.push((word >> shift) as u8);
+            }
+        }
+    }
+    ks
+}
+
+/// Encrypts or decrypts `bs` in place with the MT19937 stream cipher.
+pub fn mt19937_xor(bs: &mut ByteStream, seed: u16) {
+    let ks = keystream(u32::from(seed), bs.len());
+    bs.repeating_xor(&ks);
+}
+
+/// Recovers the 16-bit key of a ciphertext whose plaintext is known to end
+/// with `known_suffix`.
+pub fn recover_cipher_seed(ciphertext: &ByteStream, known_suffix: &ByteStream) -> Option<u16> {
+    if known_suffix.len() > ciphertext.len() {
+        return None;
+    }
+    let offset = ciphertext.len() - known_suffix.len();
+    for seed in 0..=u16::MAX {
+        let ks = keystream(u32::from(seed), ciphertext.len());
+        let matches = ciphertext
+            .iter()
+            .zip(ks.iter())
+            .skip(offset)
+            .zip(known_suffix.iter())
+            .all(|((c, k), p)| c ^ k == *p);
+        if matches {
+            return Some(seed);
+        }
+    }
+    None
+}
+
+/// Generates a password reset token the insecure way, from a generator seeded
+/// with the current time.
+pub fn generate_reset_token(len: usize) -> ByteStream {
+    keystream(unix_timestamp(), len)
+}
+
+/// Decides whether `token` was produced by a generator seeded with a
+/// timestamp in the `window` seconds up to `now`, returning that timestamp.
+pub fn detect_time_seeded_token(token: &ByteStream, now: u32, window: u32) -> Option<u32> {
+    for delta in 0..=window {
+        let seed = now.wrapping_sub(delta);
+        if keystream(seed, token.len()) == *token {
+            return Some(seed);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_the_reference_output() {
+        let mut mt = Mt19937::new(5489);
+        assert_eq!(mt.extract(), 3_499_211_612);
+        assert_eq!(mt.extract(), 581_869_302);
+        assert_eq!(mt.extract(), 3_890_346_734);
+    }
+
+    #[test]
+    fn it_matches_the_reference_output_64() {
+        let mut mt = Mt19937_64::new(5489);
+        assert_eq!(mt.extract(), 14_514_284_786_278_117_030);
+        assert_eq!(mt.extract(), 4_620_546_740_167_642_908);
+    }
+
+    #[test]
+    fn it_untempers() {
+        for &y in &[0, 1, 0xdead_beef, 0xffff_ffff, 0x1234_5678] {
+            assert_eq!(untemper(temper(y)), y);
+        }
+        for &y in &[0, 1, 0xdead_beef_cafe_babe, !0, 0x0123_4567_89ab_cdef] {
+            assert_eq!(untemper_64(temper_64(y)), y);
+        }
+    }
+
+    #[test]
+    fn it_clones_a_generator() {
+        let mut mt = Mt19937::new(0x1234);
+        let outputs: Vec<u32> = (0..N).map(|_| mt.extract()).collect();
+        let mut clone = clone_from_outputs(&outputs).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(clone.extract(), mt.extract());
+        }
+
+        let mut mt = Mt19937_64::new(0x1234);
+        let outputs: Vec<u64> = (0..N_64).map(|_| mt.extract()).collect();
+        let mut clone = clone_from_outputs_64(&outputs).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(clone.extract(), mt.extract());
+        }
+    }
+
+    #[test]
+    fn it_cracks_a_time_seed() {
+        let now = 1_500_000_000;
+        let output = Mt19937::new(now - 517).extract();
+        assert_eq!(crack_time_seed(output, now, 1000), Some(now - 517));
+    }
+
+    #[test]
+    fn it_round_trips_the_stream_cipher() {
+        let plaintext = ByteStream::from_ascii("attack at dawn, attack at dawn").unwrap();
+        let mut bs = plaintext.clone();
+        mt19937_xor(&mut bs, 0xbeef);
+        assert!(bs != plaintext);
+        mt19937_xor(&mut bs, 0xbeef);
+        assert_eq!(bs, plaintext);
+    }
+
+    #[test]
+    fn it_recovers_the_cipher_seed() {
+        let known = ByteStream::from_ascii("AAAAAAAAAAAAAA").unwrap();
+        let mut bs = keystream(unix_timestamp(), 11);
+        bs.append(&mut known.clone());
+        mt19937_xor(&mut bs, 4321);
+        assert_eq!(recover_cipher_seed(&bs, &known), Some(4321));
+    }
+
+    #[test]
+    fn it_detects_time_seeded_tokens() {
+        let now = unix_timestamp();
+        let token = generate_reset_token(16);
+        assert!(detect_time_seeded_token(&token, now + 5, 60).is_some());
+
+        let token = keystream(now - 10_000, 16);
+        assert_eq!(detect_time_seeded_token(&token, now, 60), None);
+    }
+}
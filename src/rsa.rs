@@ -0,0 +1,428 @@
+//!
+//! RSA key generation, textbook encryption, and PKCS#1 v1.5 encryption and
+//! signature padding.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use hash::Hash;
+use sha1::Sha1;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use std::fmt;
+
+/// The usual public exponent, 2^16 + 1.
+pub const DEFAULT_EXPONENT: u64 = 65537;
+
+/// The smallest amount of random padding in a PKCS#1 v1.5 encryption block.
+const MIN_PADDING: usize = 8;
+
+/// The smallest modulus `Keypair::generate` accepts. Below this there may be
+/// too few primes of each size for any pair to suit the exponent: with 8 bits
+/// only 11 and 13 qualify, and `13 - 1` is a multiple of 3.
+const MIN_MODULUS_BITS: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub enum RsaError {
+    MessageTooLong,
+    OutOfRange,
+    InvalidPadding,
+}
+
+impl fmt::Display for RsaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            RsaError::MessageTooLong => "message too long for the modulus",
+            RsaError::OutOfRange => "value not smaller than the modulus",
+            RsaError::InvalidPadding => "invalid PKCS#1 v1.5 padding",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// A hash with an ASN.1 `DigestInfo` prefix for PKCS#1 v1.5 signatures.
+pub trait DigestInfo: Hash {
+    /// The DER encoding of the `DigestInfo` up to the digest itself.
+    const DIGEST_INFO: &'static [u8];
+}
+
+impl DigestInfo for Sha1 {
+    const DIGEST_INFO: &'static [u8] = &[
+        0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+    ];
+}
+
+impl DigestInfo for Sha224 {
+    const DIGEST_INFO: &'static [u8] = &[
+        0x30, 0x2d, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x04,
+        0x05, 0x00, 0x04, 0x1c,
+    ];
+}
+
+impl DigestInfo for Sha256 {
+    const DIGEST_INFO: &'static [u8] = &[
+        0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+        0x05, 0x00, 0x04, 0x20,
+    ];
+}
+
+impl DigestInfo for Sha384 {
+    const DIGEST_INFO: &'static [u8] = &[
+        0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02,
+        0x05, 0x00, 0x04, 0x30,
+    ];
+}
+
+impl DigestInfo for Sha512 {
+    const DIGEST_INFO: &'static [u8] = &[
+        0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03,
+        0x05, 0x00, 0x04, 0x40,
+    ];
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    pub n: BigUint,
+    pub e: BigUint,
+}
+
+/// A private key, keeping the factors of the modulus so that decryption can
+/// use the Chinese remainder theorem.
+#[derive(Clone, Debug)]
+pub struct PrivateKey {
+    pub n: BigUint,
+    pub d: BigUint,
+    p: BigUint,
+    q: BigUint,
+    d_p: BigUint,
+    d_q: BigUint,
+    q_inv: BigUint,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keypair {
+    pub public: PublicKey,
+    pub private: PrivateKey,
+}
+
+impl Keypair {
+    /// Generates a key with a `bits`-bit modulus and public exponent `e`,
+    /// which must be odd and at least 3: no other exponent is ever invertible
+    /// modulo `(p - 1)(q - 1)`, so the search for primes would never end. For
+    /// the same reason the modulus must have at least 64 bits.
+    pub fn generate(bits: usize, e: u64) -> Keypair {
+        assert!(e >= 3 && e % 2 == 1, "public exponent must be odd and >= 3");
+        assert!(
+            bits >= MIN_MODULUS_BITS,
+            "modulus must have at least {} bits",
+            MIN_MODULUS_BITS
+        );
+        loop {
+            let p = BigUint::random_prime(bits / 2);
+            let q = BigUint::random_prime(bits - bits / 2);
+            if p == q {
+                continue;
+            }
+            if let Some(keypair) = Keypair::from_primes(p, q, BigUint::from(e)) {
+                return keypair;
+            }
+        }
+    }
+
+    /// Builds a key from two distinct primes, or returns `None` if `e` isn't
+    /// invertible modulo `(p - 1)(q - 1)`.
+    pub fn from_primes(p: BigUint, q: BigUint, e: BigUint) -> Option<Keypair> {
+        let one = BigUint::one();
+        let (p_minus_one, q_minus_one) = (&p - &one, &q - &one);
+        let d = e.modinv(&(&p_minus_one * &q_minus_one))?;
+        let n = &p * &q;
+        let private = PrivateKey {
+            n: n.clone(),
+            d_p: &d % &p_minus_one,
+            d_q: &d % &q_minus_one,
+            q_inv: q.modinv(&p)?,
+            d,
+            p,
+            q,
+        };
+        Some(Keypair {
+            public: PublicKey { n, e },
+            private,
+        })
+    }
+}
+
+/// The length of the modulus in bytes.
+fn modulus_len(n: &BigUint) -> usize {
+    n.bits().div_ceil(8)
+}
+
+impl PublicKey {
+    pub fn size(&self) -> usize {
+        modulus_len(&self.n)
+    }
+
+    pub fn encrypt_raw(&self, m: &BigUint) -> BigUint {
+        m.modpow(&self.e, &self.n)
+    }
+
+    /// Textbook RSA: the message is read as a big-endian integer, which must
+    /// be smaller than the modulus.
+    pub fn encrypt(&self, message: &ByteStream) -> Result<ByteStream, RsaError> {
+        let m = BigUint::from_byte_stream(message);
+        if m >= self.n {
+            return Err(RsaError::OutOfRange);
+        }
+        Ok(self.encrypt_raw(&m).to_byte_stream_padded(self.size()))
+    }
+
+    pub fn encrypt_pkcs1(&self, message: &ByteStream) -> Result<ByteStream, RsaError> {
+        let block = pkcs1_encryption_pad(message, self.size())?;
+        self.encrypt(&block)
+    }
+
+    /// Checks a PKCS#1 v1.5 signature by re-encoding the expected block and
+    /// comparing it with the whole of the recovered one.
+    pub fn verify_pkcs1<H: DigestInfo>(
+        &self,
+        message: &ByteStream,
+        signature: &ByteStream,
+    ) -> bool {
+        let block = match self.open_signature(signature) {
+            Some(block) => block,
+            None => return false,
+        };
+        match pkcs1_signature_pad::<H>(message, self.size()) {
+            Ok(expected) => expected.ct_eq(&block),
+            Err(_) => false,
+        }
+    }
+
+    /// Recovers the encoded block from a signature of the right length.
+    fn open_signature(&self, signature: &ByteStream) -> Option<ByteStream> {
+        if signature.len() != self.size() {
+            return None;
+        }
+        let s = BigUint::from_byte_stream(signature);
+        if s >= self.n {
+            return None;
+        }
+        Some(self.encrypt_raw(&s).to_byte_stream_padded(self.size()))
+    }
+}
+
+impl PrivateKey {
+    pub fn size(&self) -> usize {
+        modulus_len(&self.n)
+    }
+
+    pub fn decrypt_raw(&self, c: &BigUint) -> BigUint {
+        let m_p = c.modpow(&self.d_p, &self.p);
+        let m_q = c.modpow(&self.d_q, &self.q);
+        let h = (&self.q_inv * ((m_p + &self.p) - (&m_q % &self.p))) % &self.p;
+        m_q + h * &self.q
+    }
+
+    /// Textbook RSA decryption, returning the minimal big-endian encoding of
+    /// the recovered integer.
+    pub fn decrypt(&self, ciphertext: &ByteStream) -> Result<ByteStream, RsaError> {
+        let c = BigUint::from_byte_stream(ciphertext);
+        if c >= self.n {
+            return Err(RsaError::OutOfRange);
+        }
+        Ok(self.decrypt_raw(&c).to_byte_stream())
+    }
+
+    pub fn decrypt_pkcs1(&self, ciphertext: &ByteStream) -> Result<ByteStream, RsaError> {
+        let c = BigUint::from_byte_stream(ciphertext);
+        if ciphertext.len() != self.size() || c >= self.n {
+            return Err(RsaError::OutOfRange);
+        }
+        pkcs1_encryption_unpad(&self.decrypt_raw(&c).to_byte_stream_padded(self.size()))
+    }
+
+    pub fn sign_pkcs1<H: DigestInfo>(&self, message: &ByteStream) -> Result<ByteStream, RsaError> {
+        let block = pkcs1_signature_pad::<H>(message, self.size())?;
+        let s = self.decrypt_raw(&BigUint::from_byte_stream(&block));
+        Ok(s.to_byte_stream_padded(self.size()))
+    }
+}
+
+/// Encodes `00 02 PS 00 message` in `len` bytes, where `PS` is at least
+/// eight random nonzero bytes.
+pub fn pkcs1_encryption_pad(message: &ByteStream, len: usize) -> Result<ByteStream, RsaError> {
+    if message.len() + MIN_PADDING + 3 > len {
+        return Err(RsaError::MessageTooLong);
+    }
+    let mut block = ByteStream::from_bytes(&[0x00, 0x02]);
+    while block.len() < len - message.len() - 1 {
+        let byte = ByteStream::random(1).into_bytes()[0];
+        if byte != 0 {
+            block.push(byte);
+        }
+    }
+    block.push(0x00);
+    block.append(&mut message.clone());
+    Ok(block)
+}
+
+/// Strips `00 02 PS 00` from a decrypted block.
+pub fn pkcs1_encryption_unpad(block: &ByteStream) -> Result<ByteStream, RsaError> {
+    let bytes = block.clone().into_bytes();
+    if bytes.len() < MIN_PADDING + 3 || bytes[0] != 0x00 || bytes[1] != 0x02 {
+        return Err(RsaError::InvalidPadding);
+    }
+    match bytes[2..].iter().position(|b| *b == 0) {
+        Some(i) if i >= MIN_PADDING => Ok(ByteStream::from_bytes(&bytes[i + 3..])),
+        _ => Err(RsaError::InvalidPadding),
+    }
+}
+
+/// Encodes `00 01 FF..FF 00 DigestInfo H(message)` in `len` bytes.
+pub fn pkcs1_signature_pad<H: DigestInfo>(
+    message: &ByteStream,
+    len: usize,
+) -> Result<ByteStream, RsaError> {
+    let mut digest_info = ByteStream::from_bytes(H::DIGEST_INFO);
+    digest_info.append(&mut H::digest(message));
+    if digest_info.len() + MIN_PADDING + 3 > len {
+        return Err(RsaError::MessageTooLong);
+    }
+    let mut block = ByteStream::from_bytes(&[0x00, 0x01]);
+    while block.len() < len - digest_info.len() - 1 {
+        block.push(0xff);
+    }
+    block.push(0x00);
+    block.append(&mut digest_info);
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> ByteStream {
+        ByteStream::from_ascii("hi mom").unwrap()
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_rejects_even_exponents() {
+        Keypair::generate(256, 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_rejects_exponents_below_three() {
+        Keypair::generate(256, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_rejects_moduli_too_small_for_any_key() {
+        Keypair::generate(8, 3);
+    }
+
+    #[test]
+    fn it_generates_the_smallest_keys_it_accepts() {
+        let keypair = Keypair::generate(MIN_MODULUS_BITS, 3);
+        let m = BigUint::from(42u64);
+        let c = m.modpow(&keypair.public.e, &keypair.public.n);
+        assert_eq!(c.modpow(&keypair.private.d, &keypair.private.n), m);
+    }
+
+    #[test]
+    fn it_matches_a_textbook_example() {
+        let keypair = Keypair::from_primes(
+            BigUint::from(61u64),
+            BigUint::from(53u64),
+            BigUint::from(17u64),
+        )
+        .unwrap();
+        assert_eq!(keypair.public.n, BigUint::from(3233u64));
+        assert_eq!(keypair.private.d, BigUint::from(2753u64));
+
+        let c = keypair.public.encrypt_raw(&BigUint::from(65u64));
+        assert_eq!(c, BigUint::from(2790u64));
+        assert_eq!(keypair.private.decrypt_raw(&c), BigUint::from(65u64));
+
+        assert!(Keypair::from_primes(
+            BigUint::from(61u64),
+            BigUint::from(53u64),
+            BigUint::from(3u64)
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn it_encrypts_and_decrypts() {
+        for &e in &[3, DEFAULT_EXPONENT] {
+            let keypair = Keypair::generate(512, e);
+            assert_eq!(keypair.public.n.bits(), 512);
+
+            let c = keypair.public.encrypt(&message()).unwrap();
+            assert_eq!(c.len(), 64);
+            assert_eq!(keypair.private.decrypt(&c).unwrap(), message());
+
+            let c = keypair.public.encrypt_pkcs1(&message()).unwrap();
+            assert_ne!(c, keypair.public.encrypt_pkcs1(&message()).unwrap());
+            assert_eq!(keypair.private.decrypt_pkcs1(&c).unwrap(), message());
+        }
+    }
+
+    #[test]
+    fn it_rejects_bad_encryption_padding() {
+        let keypair = Keypair::generate(512, DEFAULT_EXPONENT);
+        let too_long = ByteStream::from(vec![0x41; 54]);
+        assert_eq!(
+            keypair.public.encrypt_pkcs1(&too_long),
+            Err(RsaError::MessageTooLong)
+        );
+
+        let unpadded = keypair.public.encrypt(&message()).unwrap();
+        assert_eq!(
+            keypair.private.decrypt_pkcs1(&unpadded),
+            Err(RsaError::InvalidPadding)
+        );
+
+        let mut short_padding = ByteStream::from_bytes(&[0x00, 0x02, 0x01, 0x00]);
+        short_padding.append(&mut ByteStream::from(vec![0x41; 60]));
+        assert_eq!(
+            pkcs1_encryption_unpad(&short_padding),
+            Err(RsaError::InvalidPadding)
+        );
+    }
+
+    #[test]
+    fn it_signs_and_verifies() {
+        let keypair = Keypair::generate(1024, DEFAULT_EXPONENT);
+        let signature = keypair.private.sign_pkcs1::<Sha256>(&message()).unwrap();
+        assert!(keypair
+            .public
+            .verify_pkcs1::<Sha256>(&message(), &signature));
+        assert!(!keypair.public.verify_pkcs1::<Sha1>(&message(), &signature));
+        assert!(!keypair
+            .public
+            .verify_pkcs1::<Sha256>(&ByteStream::from_ascii("hi dad").unwrap(), &signature));
+
+        let mut tampered = signature.clone().into_bytes();
+        tampered[10] ^= 1;
+        assert!(!keypair
+            .public
+            .verify_pkcs1::<Sha256>(&message(), &ByteStream::from(tampered)));
+    }
+
+    #[test]
+    fn it_encodes_signature_blocks() {
+        let block = pkcs1_signature_pad::<Sha1>(&ByteStream::from_ascii("abc").unwrap(), 64)
+            .unwrap()
+            .into_hex();
+        assert!(block.starts_with("0001ffff"));
+        assert!(block
+            .ends_with("003021300906052b0e03021a05000414a9993e364706816aba3e25717850c26c9cd0d89d"));
+        assert_eq!(block.len(), 128);
+        assert_eq!(
+            pkcs1_signature_pad::<Sha512>(&message(), 64),
+            Err(RsaError::MessageTooLong)
+        );
+    }
+}
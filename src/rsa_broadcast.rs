@@ -0,0 +1,58 @@
+//!
+//! Håstad's broadcast attack: the same unpadded message encrypted under `e`
+//! public keys with exponent `e` can be recovered without any private key.
+//!
+
+use bignum::{crt, BigUint};
+use byte_stream::ByteStream;
+use rsa::PublicKey;
+
+/// Recovers the message behind `ciphertexts`, which must share a public
+/// exponent `e` and number at least `e`. Returns `None` if they don't, if the
+/// moduli share a factor, or if the combined value isn't an exact `e`th power.
+pub fn broadcast_attack(ciphertexts: &[(PublicKey, ByteStream)]) -> Option<ByteStream> {
+    let e = ciphertexts.first()?.0.e.to_u64()?;
+    if e > u64::from(u32::MAX) || (ciphertexts.len() as u64) < e {
+        return None;
+    }
+    if ciphertexts
+        .iter()
+        .any(|(key, _)| key.e != ciphertexts[0].0.e)
+    {
+        return None;
+    }
+
+    let congruences: Vec<(BigUint, BigUint)> = ciphertexts
+        .iter()
+        .take(e as usize)
+        .map(|(key, c)| (BigUint::from_byte_stream(c) % &key.n, key.n.clone()))
+        .collect();
+    let power = crt(&congruences)?;
+    let m = power.nth_root(e as u32);
+    if m.pow(e as u32) == power {
+        Some(m.to_byte_stream())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::Keypair;
+
+    #[test]
+    fn it_recovers_a_broadcast_message() {
+        let message = ByteStream::from_ascii("attack at dawn").unwrap();
+        let ciphertexts: Vec<(PublicKey, ByteStream)> = (0..3)
+            .map(|_| {
+                let key = Keypair::generate(512, 3).public;
+                let c = key.encrypt(&message).unwrap();
+                (key, c)
+            })
+            .collect();
+
+        assert_eq!(broadcast_attack(&ciphertexts), Some(message));
+        assert_eq!(broadcast_attack(&ciphertexts[..2]), None);
+    }
+}
@@ -0,0 +1,95 @@
+//!
+//! Recovering an RSA plaintext from an oracle that only reveals whether the
+//! decryption of a ciphertext is even.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use rsa::{Keypair, PublicKey};
+
+pub struct Oracle {
+    keypair: Keypair,
+}
+
+impl Oracle {
+    pub fn new(keypair: Keypair) -> Oracle {
+        Oracle { keypair }
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.keypair.public
+    }
+
+    pub fn is_even(&self, ciphertext: &BigUint) -> bool {
+        self.keypair.private.decrypt_raw(ciphertext).is_even()
+    }
+}
+
+/// Recovers the plaintext behind `ciphertext` one bit of the modulus at a
+/// time.
+pub fn recover_plaintext(oracle: &Oracle, ciphertext: &ByteStream) -> ByteStream {
+    recover_plaintext_with_progress(oracle, ciphertext, |_| ())
+}
+
+/// Like `recover_plaintext`, calling `progress` with the upper bound on the
+/// plaintext after each query.
+///
+/// Multiplying the ciphertext by `2^e` doubles the plaintext; since `n` is
+/// odd, the doubled plaintext is even exactly when it didn't wrap around `n`,
+/// which says which half of the remaining interval the plaintext is in. After
+/// `i` queries the plaintext lies in `[a * n / 2^i, (a + 1) * n / 2^i)`.
+pub fn recover_plaintext_with_progress<F>(
+    oracle: &Oracle,
+    ciphertext: &ByteStream,
+    mut progress: F,
+) -> ByteStream
+where
+    F: FnMut(&BigUint),
+{
+    let key = oracle.public_key();
+    let doubler = key.encrypt_raw(&BigUint::from(2u64));
+    let mut c = BigUint::from_byte_stream(ciphertext);
+    let mut a = BigUint::zero();
+    let mut denominator = BigUint::one();
+    for _ in 0..key.n.bits() {
+        c = c * &doubler % &key.n;
+        a = a << 1;
+        denominator = denominator << 1;
+        if !oracle.is_even(&c) {
+            a = a + BigUint::one();
+        }
+        progress(&((&a + BigUint::one()) * &key.n / &denominator));
+    }
+
+    // The interval is now narrower than 1, so the plaintext is the one
+    // integer in it.
+    let lower = &a * &key.n;
+    let mut m = &lower / &denominator;
+    if !(lower % &denominator).is_zero() {
+        m = m + BigUint::one();
+    }
+    m.to_byte_stream()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::DEFAULT_EXPONENT;
+
+    #[test]
+    fn it_recovers_the_plaintext() {
+        let oracle = Oracle::new(Keypair::generate(512, DEFAULT_EXPONENT));
+        let message = ByteStream::from_ascii("Funky Cold Medina").unwrap();
+        let ciphertext = oracle.public_key().encrypt(&message).unwrap();
+
+        let mut bounds = Vec::new();
+        let recovered = recover_plaintext_with_progress(&oracle, &ciphertext, |bound| {
+            bounds.push(bound.clone())
+        });
+        assert_eq!(recovered, message);
+        assert_eq!(bounds.len(), 512);
+        assert!(bounds.windows(2).all(|w| w[1] <= w[0]));
+        assert!(bounds[511] >= BigUint::from_byte_stream(&message));
+        assert_eq!(recover_plaintext(&oracle, &ciphertext), message);
+    }
+}
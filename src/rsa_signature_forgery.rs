@@ -0,0 +1,106 @@
+//!
+//! Bleichenbacher's forgery of PKCS#1 v1.5 signatures under `e = 3`, against
+//! verifiers that stop reading the signature block once they find the hash.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use rsa::{DigestInfo, PublicKey};
+
+/// Checks a signature the sloppy way: it accepts `00 01`, any run of `FF`
+/// bytes, `00`, the `DigestInfo` and the hash, and ignores whatever follows.
+pub fn verify_lax<H: DigestInfo>(
+    key: &PublicKey,
+    message: &ByteStream,
+    signature: &ByteStream,
+) -> bool {
+    let s = BigUint::from_byte_stream(signature);
+    if signature.len() != key.size() || s >= key.n {
+        return false;
+    }
+    let block = key
+        .encrypt_raw(&s)
+        .to_byte_stream_padded(key.size())
+        .into_bytes();
+    if block[0] != 0x00 || block[1] != 0x01 {
+        return false;
+    }
+    let separator = match block[2..].iter().position(|b| *b != 0xff) {
+        Some(i) if i > 0 && block[i + 2] == 0x00 => i + 2,
+        _ => return false,
+    };
+
+    let rest = &block[separator + 1..];
+    let prefix_len = H::DIGEST_INFO.len();
+    if rest.len() < prefix_len + H::OUTPUT_SIZE || &rest[..prefix_len] != H::DIGEST_INFO {
+        return false;
+    }
+    let digest = ByteStream::from_bytes(&rest[prefix_len..prefix_len + H::OUTPUT_SIZE]);
+    digest.ct_eq(&H::digest(message))
+}
+
+/// Forges a signature that `verify_lax` accepts for `message`, by taking the
+/// `e`th root of a block with a single `FF` byte and the hash followed by
+/// enough room for the root's error. Returns `None` if the key is too small
+/// for the garbage to absorb that error.
+pub fn forge_signature<H: DigestInfo>(key: &PublicKey, message: &ByteStream) -> Option<ByteStream> {
+    let e = key.e.to_u64().filter(|e| *e <= u64::from(u32::MAX))? as u32;
+    let mut prefix = ByteStream::from_bytes(&[0x00, 0x01, 0xff, 0x00]);
+    prefix.append(&mut ByteStream::from_bytes(H::DIGEST_INFO));
+    prefix.append(&mut H::digest(message));
+    let garbage_len = key.size().checked_sub(prefix.len())?;
+
+    let mut low = prefix.clone();
+    low.append(&mut ByteStream::from(vec![0x00; garbage_len]));
+    let mut high = prefix;
+    high.append(&mut ByteStream::from(vec![0xff; garbage_len]));
+    let (low, high) = (
+        BigUint::from_byte_stream(&low),
+        BigUint::from_byte_stream(&high),
+    );
+
+    let mut root = low.nth_root(e);
+    if root.pow(e) < low {
+        root = root + BigUint::one();
+    }
+    if root.pow(e) > high {
+        return None;
+    }
+    Some(root.to_byte_stream_padded(key.size()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::Keypair;
+    use sha1::Sha1;
+    use sha2::Sha256;
+
+    #[test]
+    fn it_accepts_genuine_signatures_leniently() {
+        let keypair = Keypair::generate(1024, 3);
+        let message = ByteStream::from_ascii("hi mom").unwrap();
+        let signature = keypair.private.sign_pkcs1::<Sha1>(&message).unwrap();
+
+        assert!(verify_lax::<Sha1>(&keypair.public, &message, &signature));
+        assert!(!verify_lax::<Sha256>(&keypair.public, &message, &signature));
+        assert!(!verify_lax::<Sha1>(
+            &keypair.public,
+            &ByteStream::from_ascii("hi dad").unwrap(),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn it_forges_signatures_for_lax_verifiers_only() {
+        let keypair = Keypair::generate(1024, 3);
+        let message = ByteStream::from_ascii("hi mom").unwrap();
+        let forged = forge_signature::<Sha1>(&keypair.public, &message).unwrap();
+
+        assert!(verify_lax::<Sha1>(&keypair.public, &message, &forged));
+        assert!(!keypair.public.verify_pkcs1::<Sha1>(&message, &forged));
+
+        let small = Keypair::generate(256, 3);
+        assert_eq!(forge_signature::<Sha1>(&small.public, &message), None);
+    }
+}
@@ -0,0 +1,74 @@
+//!
+//! Recovering an unpadded RSA plaintext from a decryption service that only
+//! refuses to decrypt the exact ciphertexts it has already seen.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use rsa::{Keypair, PublicKey};
+use std::collections::HashSet;
+
+/// Decrypts textbook RSA ciphertexts, but each one only once.
+pub struct Oracle {
+    keypair: Keypair,
+    seen: HashSet<BigUint>,
+}
+
+impl Oracle {
+    pub fn new(keypair: Keypair) -> Oracle {
+        Oracle {
+            keypair,
+            seen: HashSet::new(),
+        }
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.keypair.public
+    }
+
+    /// Returns `None` for a ciphertext that is out of range or that has been
+    /// decrypted before.
+    pub fn decrypt(&mut self, ciphertext: &ByteStream) -> Option<ByteStream> {
+        let plaintext = self.keypair.private.decrypt(ciphertext).ok()?;
+        if self.seen.insert(BigUint::from_byte_stream(ciphertext)) {
+            Some(plaintext)
+        } else {
+            None
+        }
+    }
+}
+
+/// Blinds `ciphertext` as `s^e * C` for a random `s`, has the oracle decrypt
+/// that, and divides `s` back out of the result.
+pub fn recover_message(oracle: &mut Oracle, ciphertext: &ByteStream) -> Option<ByteStream> {
+    let key = oracle.public_key().clone();
+    let two = BigUint::from(2u64);
+    let (s, s_inv) = loop {
+        let s = BigUint::random_below(&(&key.n - &two)) + &two;
+        if let Some(s_inv) = s.modinv(&key.n) {
+            break (s, s_inv);
+        }
+    };
+
+    let blinded = key.encrypt_raw(&s) * BigUint::from_byte_stream(ciphertext) % &key.n;
+    let blinded_plaintext = oracle.decrypt(&blinded.to_byte_stream_padded(key.size()))?;
+    let p = BigUint::from_byte_stream(&blinded_plaintext) * s_inv % &key.n;
+    Some(p.to_byte_stream())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::DEFAULT_EXPONENT;
+
+    #[test]
+    fn it_recovers_a_message_the_oracle_refuses() {
+        let mut oracle = Oracle::new(Keypair::generate(512, DEFAULT_EXPONENT));
+        let message = ByteStream::from_ascii("{time: 1356304276, social: '555-55-5555'}").unwrap();
+        let ciphertext = oracle.public_key().encrypt(&message).unwrap();
+
+        assert_eq!(oracle.decrypt(&ciphertext), Some(message.clone()));
+        assert_eq!(oracle.decrypt(&ciphertext), None);
+        assert_eq!(recover_message(&mut oracle, &ciphertext), Some(message));
+    }
+}
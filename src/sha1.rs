@@ -0,0 +1,241 @@
+//!
+//! SHA-1, with access to the internal state so that a hasher can be resumed
+//! from a published digest.
+//!
+
+use byte_stream::ByteStream;
+use hash::Hash;
+
+const BLOCK_SIZE: usize = 64;
+
+const INITIAL_STATE: [u32; 5] = [
+    0x6745_2301,
+    0xefcd_ab89,
+    0x98ba_dcfe,
+    0x1032_5476,
+    0xc3d2_e1f0,
+];
+
+#[derive(Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: Vec<u8>,
+    len: u64,
+}
+
+impl Sha1 {
+    pub fn new() -> Sha1 {
+        Self::from_state(INITIAL_STATE, 0)
+    }
+
+    /// Resumes hashing from the given registers, as though `processed_len`
+    /// bytes (a multiple of the block size, including any padding) had
+    /// already been consumed.
+    pub fn from_state(state: [u32; 5], processed_len: u64) -> Sha1 {
+        assert!(processed_len.is_multiple_of(BLOCK_SIZE as u64));
+        Sha1 {
+            state,
+            buffer: Vec::with_capacity(BLOCK_SIZE),
+            len: processed_len,
+        }
+    }
+
+    /// Recovers the registers that produced `digest`.
+    pub fn state_from_digest(digest: &ByteStream) -> [u32; 5] {
+        assert!(digest.len() == 20);
+        let mut state = [0; 5];
+        for (s, chunk) in state.iter_mut().zip(digest.iter_blocks(4)) {
+            *s = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        state
+    }
+
+    pub fn state(&self) -> [u32; 5] {
+        self.state
+    }
+
+    /// The number of bytes hashed into `state`, which together with it is
+    /// enough to resume with `from_state`. Panics if the input so far doesn't
+    /// fill whole blocks, since the buffered bytes would be lost.
+    pub fn processed_len(&self) -> u64 {
+        assert!(self.buffer.is_empty(), "not on a block boundary");
+        self.len
+    }
+
+    /// The padding that SHA-1 appends to a message of `message_len` bytes.
+    pub fn padding(message_len: u64) -> ByteStream {
+        let mut padding = ByteStream::from_bytes(&[0x80]);
+        while (message_len as usize + padding.len()) % BLOCK_SIZE != BLOCK_SIZE - 8 {
+            padding.push(0);
+        }
+        for b in (message_len.wrapping_mul(8)).to_be_bytes().iter() {
+            padding.push(*b);
+        }
+        padding
+    }
+
+    pub fn update(&mut self, bs: &ByteStream) {
+        for b in bs.iter() {
+            self.buffer.push(*b);
+            self.len += 1;
+            if self.buffer.len() == BLOCK_SIZE {
+                let block = self.buffer.split_off(0);
+                self.process_block(&block);
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> ByteStream {
+        let padding = Self::padding(self.len);
+        self.update(&padding);
+        assert!(self.buffer.is_empty());
+
+        let mut digest = ByteStream::new();
+        for word in self.state.iter() {
+            for b in word.to_be_bytes().iter() {
+                digest.push(*b);
+            }
+        }
+        digest
+    }
+
+    fn process_block(&mut self, block: &[u8]) {
+        let mut w = [0u32; 80];
+        for (i, chunk) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+        for (i, wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        self.state[0] = self.state[0].wrapping_add(a);
+        self.state[1] = self.state[1].wrapping_add(b);
+        self.state[2] = self.state[2].wrapping_add(c);
+        self.state[3] = self.state[3].wrapping_add(d);
+        self.state[4] = self.state[4].wrapping_add(e);
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hash for Sha1 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 20;
+
+    fn new() -> Self {
+        Sha1::new()
+    }
+
+    fn update(&mut self, bs: &ByteStream) {
+        Sha1::update(self, bs)
+    }
+
+    fn finalize(self) -> ByteStream {
+        Sha1::finalize(self)
+    }
+}
+
+pub fn sha1(bs: &ByteStream) -> ByteStream {
+    Sha1::digest(bs)
+}
+
+/// The naive `SHA1(key || message)` MAC.
+pub fn secret_prefix_mac(key: &ByteStream, message: &ByteStream) -> ByteStream {
+    let mut hasher = Sha1::new();
+    hasher.update(key);
+    hasher.update(message);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_ascii(s: &str) -> String {
+        sha1(&ByteStream::from_ascii(s).unwrap()).into_hex()
+    }
+
+    #[test]
+    fn it_hashes_test_vectors() {
+        assert_eq!(hash_ascii(""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hash_ascii("abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hash_ascii("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(
+            hash_ascii("The quick brown fox jumps over the lazy dog"),
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+        );
+    }
+
+    #[test]
+    fn it_hashes_incrementally() {
+        let mut hasher = Sha1::new();
+        for chunk in &["The quick brown ", "fox jumps over", " the lazy dog"] {
+            hasher.update(&ByteStream::from_ascii(chunk).unwrap());
+        }
+        assert_eq!(
+            hasher.finalize().into_hex(),
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+        );
+    }
+
+    #[test]
+    fn it_resumes_from_a_digest() {
+        let mut message = ByteStream::from_ascii("abc").unwrap();
+        let digest = sha1(&message);
+        message.append(&mut Sha1::padding(3));
+        let mut suffix = ByteStream::from_ascii("def").unwrap();
+
+        let mut hasher = Sha1::from_state(Sha1::state_from_digest(&digest), message.len() as u64);
+        hasher.update(&suffix);
+
+        message.append(&mut suffix);
+        assert_eq!(hasher.finalize(), sha1(&message));
+    }
+
+    #[test]
+    fn it_exports_state_on_block_boundaries() {
+        let mut hasher = Sha1::new();
+        hasher.update(&ByteStream::from(vec![b'a'; BLOCK_SIZE]));
+        assert_eq!(hasher.processed_len(), BLOCK_SIZE as u64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_refuses_to_export_state_mid_block() {
+        let mut hasher = Sha1::new();
+        hasher.update(&ByteStream::from(vec![b'a'; BLOCK_SIZE]));
+        hasher.update(&ByteStream::from_ascii("abc").unwrap());
+        hasher.processed_len();
+    }
+}
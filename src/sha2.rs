@@ -0,0 +1,625 @@
+//!
+//! The SHA-2 family of hash functions (FIPS 180-4). Each hasher can export its
+//! internal state and be resumed from one.
+//!
+
+use byte_stream::ByteStream;
+use hash::Hash;
+
+const SHA224_INITIAL_STATE: [u32; 8] = [
+    0xc105_9ed8,
+    0x367c_d507,
+    0x3070_dd17,
+    0xf70e_5939,
+    0xffc0_0b31,
+    0x6858_1511,
+    0x64f9_8fa7,
+    0xbefa_4fa4,
+];
+
+const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+const SHA384_INITIAL_STATE: [u64; 8] = [
+    0xcbbb_9d5d_c105_9ed8,
+    0x629a_292a_367c_d507,
+    0x9159_015a_3070_dd17,
+    0x152f_ecd8_f70e_5939,
+    0x6733_2667_ffc0_0b31,
+    0x8eb4_4a87_6858_1511,
+    0xdb0c_2e0d_64f9_8fa7,
+    0x47b5_481d_befa_4fa4,
+];
+
+const SHA512_INITIAL_STATE: [u64; 8] = [
+    0x6a09_e667_f3bc_c908,
+    0xbb67_ae85_84ca_a73b,
+    0x3c6e_f372_fe94_f82b,
+    0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1,
+    0x9b05_688c_2b3e_6c1f,
+    0x1f83_d9ab_fb41_bd6b,
+    0x5be0_cd19_137e_2179,
+];
+
+const K_256: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+const K_512: [u64; 80] = [
+    0x428a_2f98_d728_ae22,
+    0x7137_4491_23ef_65cd,
+    0xb5c0_fbcf_ec4d_3b2f,
+    0xe9b5_dba5_8189_dbbc,
+    0x3956_c25b_f348_b538,
+    0x59f1_11f1_b605_d019,
+    0x923f_82a4_af19_4f9b,
+    0xab1c_5ed5_da6d_8118,
+    0xd807_aa98_a303_0242,
+    0x1283_5b01_4570_6fbe,
+    0x2431_85be_4ee4_b28c,
+    0x550c_7dc3_d5ff_b4e2,
+    0x72be_5d74_f27b_896f,
+    0x80de_b1fe_3b16_96b1,
+    0x9bdc_06a7_25c7_1235,
+    0xc19b_f174_cf69_2694,
+    0xe49b_69c1_9ef1_4ad2,
+    0xefbe_4786_384f_25e3,
+    0x0fc1_9dc6_8b8c_d5b5,
+    0x240c_a1cc_77ac_9c65,
+    0x2de9_2c6f_592b_0275,
+    0x4a74_84aa_6ea6_e483,
+    0x5cb0_a9dc_bd41_fbd4,
+    0x76f9_88da_8311_53b5,
+    0x983e_5152_ee66_dfab,
+    0xa831_c66d_2db4_3210,
+    0xb003_27c8_98fb_213f,
+    0xbf59_7fc7_beef_0ee4,
+    0xc6e0_0bf3_3da8_8fc2,
+    0xd5a7_9147_930a_a725,
+    0x06ca_6351_e003_826f,
+    0x1429_2967_0a0e_6e70,
+    0x27b7_0a85_46d2_2ffc,
+    0x2e1b_2138_5c26_c926,
+    0x4d2c_6dfc_5ac4_2aed,
+    0x5338_0d13_9d95_b3df,
+    0x650a_7354_8baf_63de,
+    0x766a_0abb_3c77_b2a8,
+    0x81c2_c92e_47ed_aee6,
+    0x9272_2c85_1482_353b,
+    0xa2bf_e8a1_4cf1_0364,
+    0xa81a_664b_bc42_3001,
+    0xc24b_8b70_d0f8_9791,
+    0xc76c_51a3_0654_be30,
+    0xd192_e819_d6ef_5218,
+    0xd699_0624_5565_a910,
+    0xf40e_3585_5771_202a,
+    0x106a_a070_32bb_d1b8,
+    0x19a4_c116_b8d2_d0c8,
+    0x1e37_6c08_5141_ab53,
+    0x2748_774c_df8e_eb99,
+    0x34b0_bcb5_e19b_48a8,
+    0x391c_0cb3_c5c9_5a63,
+    0x4ed8_aa4a_e341_8acb,
+    0x5b9c_ca4f_7763_e373,
+    0x682e_6ff3_d6b2_b8a3,
+    0x748f_82ee_5def_b2fc,
+    0x78a5_636f_4317_2f60,
+    0x84c8_7814_a1f0_ab72,
+    0x8cc7_0208_1a64_39ec,
+    0x90be_fffa_2363_1e28,
+    0xa450_6ceb_de82_bde9,
+    0xbef9_a3f7_b2c6_7915,
+    0xc671_78f2_e372_532b,
+    0xca27_3ece_ea26_619c,
+    0xd186_b8c7_21c0_c207,
+    0xeada_7dd6_cde0_eb1e,
+    0xf57d_4f7f_ee6e_d178,
+    0x06f0_67aa_7217_6fba,
+    0x0a63_7dc5_a2c8_98a6,
+    0x113f_9804_bef9_0dae,
+    0x1b71_0b35_131c_471b,
+    0x28db_77f5_2304_7d84,
+    0x32ca_ab7b_40c7_2493,
+    0x3c9e_be0a_15c9_bebc,
+    0x431d_67c4_9c10_0d4c,
+    0x4cc5_d4be_cb3e_42b6,
+    0x597f_299c_fc65_7e2a,
+    0x5fcb_6fab_3ad6_faec,
+    0x6c44_198c_4a47_5817,
+];
+
+/// The compression function and buffering shared by SHA-224 and SHA-256.
+#[derive(Clone)]
+struct Engine256 {
+    state: [u32; 8],
+    buffer: Vec<u8>,
+    len: u64,
+}
+
+impl Engine256 {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new(state: [u32; 8], processed_len: u64) -> Engine256 {
+        assert!(processed_len.is_multiple_of(Self::BLOCK_SIZE as u64));
+        Engine256 {
+            state,
+            buffer: Vec::with_capacity(Self::BLOCK_SIZE),
+            len: processed_len,
+        }
+    }
+
+    fn padding(message_len: u64) -> ByteStream {
+        let mut padding = ByteStream::from_bytes(&[0x80]);
+        while (message_len as usize + padding.len()) % Self::BLOCK_SIZE != Self::BLOCK_SIZE - 8 {
+            padding.push(0);
+        }
+        for b in (message_len.wrapping_mul(8)).to_be_bytes().iter() {
+            padding.push(*b);
+        }
+        padding
+    }
+
+    fn update(&mut self, bs: &ByteStream) {
+        for b in bs.iter() {
+            self.buffer.push(*b);
+            self.len += 1;
+            if self.buffer.len() == Self::BLOCK_SIZE {
+                let block = self.buffer.split_off(0);
+                self.process_block(&block);
+            }
+        }
+    }
+
+    fn finalize(mut self, output_size: usize) -> ByteStream {
+        let padding = Self::padding(self.len);
+        self.update(&padding);
+        assert!(self.buffer.is_empty());
+
+        let mut digest = ByteStream::new();
+        for word in self.state.iter() {
+            for b in word.to_be_bytes().iter() {
+                if digest.len() < output_size {
+                    digest.push(*b);
+                }
+            }
+        }
+        digest
+    }
+
+    fn process_block(&mut self, block: &[u8]) {
+        let mut w = [0u32; 64];
+        for (i, chunk) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (ki, wi) in K_256.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*ki)
+                .wrapping_add(*wi);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+}
+
+/// The compression function and buffering shared by SHA-384 and SHA-512.
+#[derive(Clone)]
+struct Engine512 {
+    state: [u64; 8],
+    buffer: Vec<u8>,
+    len: u64,
+}
+
+impl Engine512 {
+    const BLOCK_SIZE: usize = 128;
+
+    fn new(state: [u64; 8], processed_len: u64) -> Engine512 {
+        assert!(processed_len.is_multiple_of(Self::BLOCK_SIZE as u64));
+        Engine512 {
+            state,
+            buffer: Vec::with_capacity(Self::BLOCK_SIZE),
+            len: processed_len,
+        }
+    }
+
+    fn padding(message_len: u64) -> ByteStream {
+        let mut padding = ByteStream::from_bytes(&[0x80]);
+        while (message_len as usize + padding.len()) % Self::BLOCK_SIZE != Self::BLOCK_SIZE - 16 {
+            padding.push(0);
+        }
+        for b in (u128::from(message_len) * 8).to_be_bytes().iter() {
+            padding.push(*b);
+        }
+        padding
+    }
+
+    fn update(&mut self, bs: &ByteStream) {
+        for b in bs.iter() {
+            self.buffer.push(*b);
+            self.len += 1;
+            if self.buffer.len() == Self::BLOCK_SIZE {
+                let block = self.buffer.split_off(0);
+                self.process_block(&block);
+            }
+        }
+    }
+
+    fn finalize(mut self, output_size: usize) -> ByteStream {
+        let padding = Self::padding(self.len);
+        self.update(&padding);
+        assert!(self.buffer.is_empty());
+
+        let mut digest = ByteStream::new();
+        for word in self.state.iter() {
+            for b in word.to_be_bytes().iter() {
+                if digest.len() < output_size {
+                    digest.push(*b);
+                }
+            }
+        }
+        digest
+    }
+
+    fn process_block(&mut self, block: &[u8]) {
+        let mut w = [0u64; 80];
+        for (i, chunk) in block.chunks(8).enumerate() {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            w[i] = u64::from_be_bytes(bytes);
+        }
+        for i in 16..80 {
+            let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+            let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (ki, wi) in K_512.iter().zip(w.iter()) {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*ki)
+                .wrapping_add(*wi);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+}
+
+macro_rules! sha2_hasher {
+    ($name:ident, $engine:ident, $word:ty, $initial_state:expr, $output_size:expr) => {
+        #[derive(Clone)]
+        pub struct $name {
+            engine: $engine,
+        }
+
+        impl $name {
+            pub fn new() -> $name {
+                Self::from_state($initial_state, 0)
+            }
+
+            /// Resumes hashing from the given registers, as though
+            /// `processed_len` bytes (a multiple of the block size, including
+            /// any padding) had already been consumed.
+            pub fn from_state(state: [$word; 8], processed_len: u64) -> $name {
+                $name {
+                    engine: $engine::new(state, processed_len),
+                }
+            }
+
+            /// The registers after the last complete block. Together with
+            /// `processed_len` this is enough to resume with `from_state`.
+            pub fn state(&self) -> [$word; 8] {
+                self.engine.state
+            }
+
+            /// The number of bytes hashed into `state`, which together with
+            /// it is enough to resume with `from_state`. Panics if the input
+            /// so far doesn't fill whole blocks, since the buffered bytes
+            /// would be lost.
+            pub fn processed_len(&self) -> u64 {
+                assert!(self.engine.buffer.is_empty(), "not on a block boundary");
+                self.engine.len
+            }
+
+            /// The padding appended to a message of `message_len` bytes.
+            pub fn padding(message_len: u64) -> ByteStream {
+                $engine::padding(message_len)
+            }
+
+            pub fn update(&mut self, bs: &ByteStream) {
+                self.engine.update(bs);
+            }
+
+            pub fn finalize(self) -> ByteStream {
+                self.engine.finalize($output_size)
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Hash for $name {
+            const BLOCK_SIZE: usize = $engine::BLOCK_SIZE;
+            const OUTPUT_SIZE: usize = $output_size;
+
+            fn new() -> Self {
+                $name::new()
+            }
+
+            fn update(&mut self, bs: &ByteStream) {
+                $name::update(self, bs)
+            }
+
+            fn finalize(self) -> ByteStream {
+                $name::finalize(self)
+            }
+        }
+    };
+}
+
+sha2_hasher!(Sha224, Engine256, u32, SHA224_INITIAL_STATE, 28);
+sha2_hasher!(Sha256, Engine256, u32, SHA256_INITIAL_STATE, 32);
+sha2_hasher!(Sha384, Engine512, u64, SHA384_INITIAL_STATE, 48);
+sha2_hasher!(Sha512, Engine512, u64, SHA512_INITIAL_STATE, 64);
+
+impl Sha256 {
+    /// Recovers the registers that produced `digest`.
+    pub fn state_from_digest(digest: &ByteStream) -> [u32; 8] {
+        assert!(digest.len() == 32);
+        let mut state = [0; 8];
+        for (s, chunk) in state.iter_mut().zip(digest.iter_blocks(4)) {
+            *s = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        state
+    }
+}
+
+impl Sha512 {
+    /// Recovers the registers that produced `digest`.
+    pub fn state_from_digest(digest: &ByteStream) -> [u64; 8] {
+        assert!(digest.len() == 64);
+        let mut state = [0; 8];
+        for (s, chunk) in state.iter_mut().zip(digest.iter_blocks(8)) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            *s = u64::from_be_bytes(bytes);
+        }
+        state
+    }
+}
+
+pub fn sha224(bs: &ByteStream) -> ByteStream {
+    Sha224::digest(bs)
+}
+
+pub fn sha256(bs: &ByteStream) -> ByteStream {
+    Sha256::digest(bs)
+}
+
+pub fn sha384(bs: &ByteStream) -> ByteStream {
+    Sha384::digest(bs)
+}
+
+pub fn sha512(bs: &ByteStream) -> ByteStream {
+    Sha512::digest(bs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_BLOCK: &str = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const TWO_BLOCK: &str = "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    fn hash_ascii<H: Hash>(s: &str) -> String {
+        H::digest(&ByteStream::from_ascii(s).unwrap()).into_hex()
+    }
+
+    #[test]
+    fn it_hashes_sha224_vectors() {
+        assert_eq!(
+            hash_ascii::<Sha224>(""),
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f"
+        );
+        assert_eq!(
+            hash_ascii::<Sha224>("abc"),
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+        );
+        assert_eq!(
+            hash_ascii::<Sha224>(ONE_BLOCK),
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"
+        );
+    }
+
+    #[test]
+    fn it_hashes_sha256_vectors() {
+        assert_eq!(
+            hash_ascii::<Sha256>(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash_ascii::<Sha256>("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_ascii::<Sha256>(ONE_BLOCK),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn it_hashes_sha384_vectors() {
+        assert_eq!(
+            hash_ascii::<Sha384>(""),
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b"
+        );
+        assert_eq!(
+            hash_ascii::<Sha384>("abc"),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+        );
+        assert_eq!(
+            hash_ascii::<Sha384>(TWO_BLOCK),
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"
+        );
+    }
+
+    #[test]
+    fn it_hashes_sha512_vectors() {
+        assert_eq!(
+            hash_ascii::<Sha512>(""),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+        assert_eq!(
+            hash_ascii::<Sha512>("abc"),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            hash_ascii::<Sha512>(TWO_BLOCK),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+    }
+
+    #[test]
+    fn it_exports_and_imports_state() {
+        let message = ByteStream::from_ascii(TWO_BLOCK).unwrap();
+        let bytes = message.clone().into_bytes();
+        let first = ByteStream::from_bytes(&bytes[..64]);
+        let rest = ByteStream::from_bytes(&bytes[64..]);
+
+        let mut hasher = Sha256::new();
+        hasher.update(&first);
+        let mut resumed = Sha256::from_state(hasher.state(), hasher.processed_len());
+        resumed.update(&rest);
+        assert_eq!(resumed.finalize(), sha256(&message));
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_refuses_to_export_state_mid_block() {
+        let mut hasher = Sha256::new();
+        hasher.update(&ByteStream::from_ascii("abc").unwrap());
+        hasher.processed_len();
+    }
+}
@@ -0,0 +1,588 @@
+//!
+//! The Secure Remote Password protocol (SRP-6a) over SHA-256, served over a
+//! loopback TCP connection, along with the attacks on a server that doesn't
+//! validate public keys and on a simplified variant of the protocol.
+//!
+//! The protocol states are carried by the types rather than by an explicit
+//! state enum. The server can only check a proof through the `Session` that
+//! `Server::challenge` returns, and the check consumes it, so a challenge can
+//! be answered once and never before it is issued. The client has nothing to
+//! remember between its two messages beyond what `Client` already holds.
+//! Messages out of order can't be expressed, rather than being rejected at
+//! run time.
+//!
+
+use bignum::BigUint;
+use byte_stream::ByteStream;
+use dh::{Group, Keypair};
+use hmac::hmac;
+use sha2::{sha256, Sha256};
+use std::collections::HashMap;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+const SALT_SIZE: usize = 16;
+
+/// The bit length of the scrambling parameter in simplified SRP.
+const SIMPLE_U_BITS: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameters {
+    pub group: Group,
+    pub k: BigUint,
+}
+
+impl Parameters {
+    /// Uses the SRP-6a multiplier `k = H(N || PAD(g))`.
+    pub fn new(group: Group) -> Parameters {
+        let n = group.p.to_byte_stream();
+        let k = hash(&[n.clone(), group.g.to_byte_stream_padded(n.len())]);
+        Parameters { group, k }
+    }
+
+    /// The 1536-bit MODP group with `g = 2`.
+    pub fn modp_1536() -> Parameters {
+        Parameters::new(Group::modp(1536).unwrap())
+    }
+
+    fn n(&self) -> &BigUint {
+        &self.group.p
+    }
+
+    fn g(&self) -> &BigUint {
+        &self.group.g
+    }
+
+    fn pad(&self, x: &BigUint) -> ByteStream {
+        x.to_byte_stream_padded(self.n().to_byte_stream().len())
+    }
+
+    /// The scrambling parameter `u = H(PAD(A) || PAD(B))`.
+    fn scrambler(&self, client_public: &BigUint, server_public: &BigUint) -> BigUint {
+        hash(&[self.pad(client_public), self.pad(server_public)])
+    }
+}
+
+fn hash(parts: &[ByteStream]) -> BigUint {
+    let mut input = ByteStream::new();
+    for part in parts {
+        input.append(&mut part.clone());
+    }
+    BigUint::from_byte_stream(&sha256(&input))
+}
+
+/// The password-derived exponent `x = H(salt || H(email || ":" || password))`,
+/// as in RFC 5054, over the UTF-8 encoding of the email and password.
+pub fn private_key(salt: &ByteStream, email: &str, password: &str) -> BigUint {
+    let identity = ByteStream::from_bytes(format!("{}:{}", email, password).as_bytes());
+    hash(&[salt.clone(), sha256(&identity)])
+}
+
+/// The proof that a party knows the shared secret: `HMAC(H(S), salt)`.
+pub fn proof(secret: &BigUint, salt: &ByteStream) -> ByteStream {
+    hmac::<Sha256>(&sha256(&secret.to_byte_stream()), salt)
+}
+
+/// What the server stores in place of a password.
+#[derive(Clone, Debug)]
+pub struct Verifier {
+    pub salt: ByteStream,
+    pub v: BigUint,
+}
+
+impl Verifier {
+    pub fn new(params: &Parameters, email: &str, password: &str) -> Verifier {
+        let salt = ByteStream::random(SALT_SIZE);
+        let x = private_key(&salt, email, password);
+        let v = params.g().modpow(&x, params.n());
+        Verifier { salt, v }
+    }
+}
+
+pub struct Client {
+    params: Parameters,
+    email: String,
+    password: String,
+    keypair: Keypair,
+}
+
+impl Client {
+    pub fn new(params: Parameters, email: &str, password: &str) -> Client {
+        let keypair = params.group.generate_keypair();
+        Client {
+            params,
+            email: email.to_string(),
+            password: password.to_string(),
+            keypair,
+        }
+    }
+
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    pub fn public(&self) -> &BigUint {
+        &self.keypair.public
+    }
+
+    /// Answers the server's challenge with a proof of the shared secret, or
+    /// `None` if the server's public key or the scrambler is degenerate.
+    pub fn respond(&self, salt: &ByteStream, server_public: &BigUint) -> Option<ByteStream> {
+        let n = self.params.n();
+        let server_public = server_public % n;
+        let u = self.params.scrambler(self.public(), &server_public);
+        if server_public.is_zero() || u.is_zero() {
+            return None;
+        }
+
+        let x = private_key(salt, &self.email, &self.password);
+        let kgx = (&self.params.k * self.params.g().modpow(&x, n)) % n;
+        let base = (server_public + n - kgx) % n;
+        let secret = base.modpow(&(&self.keypair.private + u * x), n);
+        Some(proof(&secret, salt))
+    }
+}
+
+pub struct Server {
+    params: Parameters,
+    verifiers: HashMap<String, Verifier>,
+    check_public_keys: bool,
+}
+
+impl Server {
+    pub fn new(params: Parameters) -> Server {
+        Server {
+            params,
+            verifiers: HashMap::new(),
+            check_public_keys: true,
+        }
+    }
+
+    /// A server that skips SRP-6a's check that `A mod N != 0`, and so lets
+    /// anyone log in with a zero key.
+    pub fn without_public_key_check(params: Parameters) -> Server {
+        Server {
+            check_public_keys: false,
+            ..Server::new(params)
+        }
+    }
+
+    pub fn register(&mut self, email: &str, password: &str) {
+        let verifier = Verifier::new(&self.params, email, password);
+        self.verifiers.insert(email.to_string(), verifier);
+    }
+
+    /// Starts a login, or returns `None` if the email is unknown or the
+    /// client's public key is rejected.
+    pub fn challenge(&self, email: &str, client_public: &BigUint) -> Option<Session> {
+        let verifier = self.verifiers.get(email)?;
+        let n = self.params.n();
+        let client_public = client_public % n;
+        if self.check_public_keys && client_public.is_zero() {
+            return None;
+        }
+
+        let keypair = self.params.group.generate_keypair();
+        let public = (&self.params.k * &verifier.v + &keypair.public) % n;
+        let u = self.params.scrambler(&client_public, &public);
+        let secret = (client_public * verifier.v.modpow(&u, n) % n).modpow(&keypair.private, n);
+        Some(Session {
+            salt: verifier.salt.clone(),
+            public,
+            secret,
+        })
+    }
+}
+
+/// The server's half of a login in progress.
+pub struct Session {
+    salt: ByteStream,
+    public: BigUint,
+    secret: BigUint,
+}
+
+impl Session {
+    pub fn salt(&self) -> &ByteStream {
+        &self.salt
+    }
+
+    pub fn public(&self) -> &BigUint {
+        &self.public
+    }
+
+    /// Checks the client's proof, ending the login either way.
+    pub fn verify(self, client_proof: &ByteStream) -> bool {
+        proof(&self.secret, &self.salt).ct_eq(client_proof)
+    }
+}
+
+/// Serves logins over TCP with a line-based protocol:
+///
+/// ```text
+/// > HELLO <email> <A>
+/// < CHALLENGE <salt> <B>
+/// > PROOF <proof>
+/// < OK
+/// ```
+///
+/// Numbers and byte strings are hex encoded, and any failure is answered
+/// with `FAIL`.
+pub struct Listener {
+    listener: TcpListener,
+    server: Server,
+}
+
+impl Listener {
+    /// Binds to `addr`; use port 0 to let the operating system pick one.
+    pub fn bind(addr: &str, server: Server) -> io::Result<Listener> {
+        Ok(Listener {
+            listener: TcpListener::bind(addr)?,
+            server,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Handles logins one connection at a time until the listener fails.
+    pub fn serve(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            // A client that hangs up early shouldn't take the server down.
+            let _ = self.handle(stream?);
+        }
+        Ok(())
+    }
+
+    /// Serves logins on a background thread.
+    pub fn spawn(self) -> thread::JoinHandle<io::Result<()>> {
+        thread::spawn(move || self.serve())
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let hello = read_fields(&mut reader)?;
+        let session = match (
+            hello.first().map(String::as_str),
+            hello.get(1),
+            hello.get(2),
+        ) {
+            (Some("HELLO"), Some(email), Some(public)) => BigUint::from_hex(public)
+                .ok()
+                .and_then(|public| self.server.challenge(email, &public)),
+            _ => None,
+        };
+        let session = match session {
+            Some(session) => session,
+            None => return writeln!(stream, "FAIL"),
+        };
+        writeln!(
+            stream,
+            "CHALLENGE {} {}",
+            session.salt().clone().into_hex(),
+            session.public().to_hex()
+        )?;
+
+        let answer = read_fields(&mut reader)?;
+        let verified = match (answer.first().map(String::as_str), answer.get(1)) {
+            (Some("PROOF"), Some(proof)) => ByteStream::from_hex(proof)
+                .map(|proof| session.verify(&proof))
+                .unwrap_or(false),
+            _ => false,
+        };
+        writeln!(stream, "{}", if verified { "OK" } else { "FAIL" })
+    }
+}
+
+fn read_fields<R: BufRead>(reader: &mut R) -> io::Result<Vec<String>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    Ok(line.trim_end().split(' ').map(String::from).collect())
+}
+
+/// Logs in to the server at `addr` announcing `public` as the client's key
+/// and answering the challenge with `respond`, returning whether the server
+/// accepted the proof.
+pub fn authenticate<F>(
+    addr: &SocketAddr,
+    email: &str,
+    public: &BigUint,
+    respond: F,
+) -> io::Result<bool>
+where
+    F: FnOnce(&ByteStream, &BigUint) -> Option<ByteStream>,
+{
+    let mut stream = TcpStream::connect(addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    writeln!(stream, "HELLO {} {}", email, public.to_hex())?;
+
+    let challenge = read_fields(&mut reader)?;
+    let (salt, server_public) = match (
+        challenge.first().map(String::as_str),
+        challenge.get(1),
+        challenge.get(2),
+    ) {
+        (Some("CHALLENGE"), Some(salt), Some(public)) => {
+            match (ByteStream::from_hex(salt), BigUint::from_hex(public)) {
+                (Ok(salt), Ok(public)) => (salt, public),
+                _ => return Ok(false),
+            }
+        }
+        _ => return Ok(false),
+    };
+    let proof = match respond(&salt, &server_public) {
+        Some(proof) => proof,
+        None => return Ok(false),
+    };
+    writeln!(stream, "PROOF {}", proof.into_hex())?;
+
+    let result = read_fields(&mut reader)?;
+    Ok(result.first().map(String::as_str) == Some("OK"))
+}
+
+pub fn login(addr: &SocketAddr, client: &Client) -> io::Result<bool> {
+    authenticate(
+        addr,
+        client.email(),
+        client.public(),
+        |salt, server_public| client.respond(salt, server_public),
+    )
+}
+
+/// Logs in without the password by announcing `A = multiple * N`, which
+/// forces the server's shared secret to zero unless it checks for that.
+pub fn zero_key_login(
+    addr: &SocketAddr,
+    params: &Parameters,
+    email: &str,
+    multiple: u64,
+) -> io::Result<bool> {
+    let public = params.n() * BigUint::from(multiple);
+    authenticate(addr, email, &public, |salt, _| {
+        Some(proof(&BigUint::zero(), salt))
+    })
+}
+
+/// The server's message in simplified SRP, where `B = g^b` doesn't depend on
+/// the password and `u` is a random number rather than a hash.
+#[derive(Clone, Debug)]
+pub struct SimpleChallenge {
+    pub salt: ByteStream,
+    pub public: BigUint,
+    pub u: BigUint,
+}
+
+pub struct SimpleClient {
+    params: Parameters,
+    email: String,
+    password: String,
+    keypair: Keypair,
+}
+
+impl SimpleClient {
+    pub fn new(params: Parameters, email: &str, password: &str) -> SimpleClient {
+        let keypair = params.group.generate_keypair();
+        SimpleClient {
+            params,
+            email: email.to_string(),
+            password: password.to_string(),
+            keypair,
+        }
+    }
+
+    pub fn public(&self) -> &BigUint {
+        &self.keypair.public
+    }
+
+    /// Computes `S = B^(a + u * x)` and proves knowledge of it.
+    pub fn respond(&self, challenge: &SimpleChallenge) -> ByteStream {
+        let x = private_key(&challenge.salt, &self.email, &self.password);
+        let exponent = &self.keypair.private + &challenge.u * x;
+        let secret = challenge.public.modpow(&exponent, self.params.n());
+        proof(&secret, &challenge.salt)
+    }
+}
+
+pub struct SimpleServer {
+    params: Parameters,
+    verifier: Verifier,
+}
+
+impl SimpleServer {
+    pub fn new(params: Parameters, email: &str, password: &str) -> SimpleServer {
+        let verifier = Verifier::new(&params, email, password);
+        SimpleServer { params, verifier }
+    }
+
+    /// Starts a login, returning the challenge and the shared secret
+    /// `S = (A * v^u)^b`.
+    pub fn challenge(&self, client_public: &BigUint) -> (SimpleChallenge, BigUint) {
+        let n = self.params.n();
+        let keypair = self.params.group.generate_keypair();
+        let u = BigUint::random_below(&(BigUint::one() << SIMPLE_U_BITS));
+        let base = client_public * self.verifier.v.modpow(&u, n) % n;
+        let secret = base.modpow(&keypair.private, n);
+        let challenge = SimpleChallenge {
+            salt: self.verifier.salt.clone(),
+            public: keypair.public,
+            u,
+        };
+        (challenge, secret)
+    }
+
+    pub fn verify(&self, secret: &BigUint, client_proof: &ByteStream) -> bool {
+        proof(secret, &self.verifier.salt).ct_eq(client_proof)
+    }
+}
+
+/// A server that has stolen nothing but can pick its own challenge: with an
+/// empty salt, `b = 1` and `u = 1`, the client's proof commits to
+/// `S = A * g^x`, which can be checked against password guesses offline.
+pub struct MaliciousSimpleServer {
+    params: Parameters,
+}
+
+impl MaliciousSimpleServer {
+    pub fn new(params: Parameters) -> MaliciousSimpleServer {
+        MaliciousSimpleServer { params }
+    }
+
+    pub fn challenge(&self) -> SimpleChallenge {
+        SimpleChallenge {
+            salt: ByteStream::new(),
+            public: self.params.g().clone(),
+            u: BigUint::one(),
+        }
+    }
+
+    /// Finds the password in `dictionary` that produced `client_proof`.
+    pub fn crack<'a, I>(
+        &self,
+        email: &str,
+        client_public: &BigUint,
+        client_proof: &ByteStream,
+        dictionary: I,
+    ) -> Option<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let n = self.params.n();
+        let salt = ByteStream::new();
+        dictionary.into_iter().find(|password| {
+            let x = private_key(&salt, email, password);
+            let secret = client_public * self.params.g().modpow(&x, n) % n;
+            proof(&secret, &salt).ct_eq(client_proof)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMAIL: &str = "alice@example.com";
+    const PASSWORD: &str = "correct horse";
+
+    fn spawn(server: Server) -> SocketAddr {
+        let listener = Listener::bind("127.0.0.1:0", server).unwrap();
+        let addr = listener.local_addr().unwrap();
+        listener.spawn();
+        addr
+    }
+
+    #[test]
+    fn it_agrees_on_a_secret() {
+        let params = Parameters::modp_1536();
+        let mut server = Server::new(params.clone());
+        server.register(EMAIL, PASSWORD);
+
+        let client = Client::new(params.clone(), EMAIL, PASSWORD);
+        let session = server.challenge(EMAIL, client.public()).unwrap();
+        let answer = client.respond(session.salt(), session.public()).unwrap();
+        assert!(session.verify(&answer));
+
+        let impostor = Client::new(params, EMAIL, "battery staple");
+        let session = server.challenge(EMAIL, impostor.public()).unwrap();
+        let answer = impostor.respond(session.salt(), session.public()).unwrap();
+        assert!(!session.verify(&answer));
+
+        assert!(server
+            .challenge("bob@example.com", client.public())
+            .is_none());
+    }
+
+    #[test]
+    fn it_hashes_passwords_as_utf8() {
+        let salt = ByteStream::random(SALT_SIZE);
+        let keys: Vec<BigUint> = ["A", "Ł", "e", "é", "été"]
+            .iter()
+            .map(|password| private_key(&salt, EMAIL, password))
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert!(keys[..i].iter().all(|other| other != key));
+        }
+    }
+
+    #[test]
+    fn it_logs_in_over_tcp() {
+        let params = Parameters::modp_1536();
+        let mut server = Server::new(params.clone());
+        server.register(EMAIL, PASSWORD);
+        let addr = spawn(server);
+
+        assert!(login(&addr, &Client::new(params.clone(), EMAIL, PASSWORD)).unwrap());
+        assert!(!login(&addr, &Client::new(params, EMAIL, "battery staple")).unwrap());
+    }
+
+    #[test]
+    fn it_bypasses_a_server_that_accepts_zero_keys() {
+        let params = Parameters::modp_1536();
+        let mut careless = Server::without_public_key_check(params.clone());
+        careless.register(EMAIL, PASSWORD);
+        let mut careful = Server::new(params.clone());
+        careful.register(EMAIL, PASSWORD);
+        let careless = spawn(careless);
+        let careful = spawn(careful);
+
+        for multiple in 0..3 {
+            assert!(zero_key_login(&careless, &params, EMAIL, multiple).unwrap());
+            assert!(!zero_key_login(&careful, &params, EMAIL, multiple).unwrap());
+        }
+    }
+
+    #[test]
+    fn it_agrees_on_a_secret_in_simplified_srp() {
+        let params = Parameters::modp_1536();
+        let server = SimpleServer::new(params.clone(), EMAIL, PASSWORD);
+        let client = SimpleClient::new(params, EMAIL, PASSWORD);
+
+        let (challenge, secret) = server.challenge(client.public());
+        assert!(server.verify(&secret, &client.respond(&challenge)));
+    }
+
+    #[test]
+    fn it_cracks_simplified_srp_offline() {
+        let params = Parameters::modp_1536();
+        let server = MaliciousSimpleServer::new(params.clone());
+        let client = SimpleClient::new(params, EMAIL, "sunshine");
+        let answer = client.respond(&server.challenge());
+
+        let dictionary = ["password", "123456", "letmein", "sunshine", "qwerty"];
+        assert_eq!(
+            server.crack(EMAIL, client.public(), &answer, dictionary.iter().cloned()),
+            Some("sunshine")
+        );
+        assert_eq!(
+            server.crack(
+                EMAIL,
+                client.public(),
+                &answer,
+                dictionary[..3].iter().cloned()
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//!
+//! A loopback HTTP server that checks HMAC-SHA1 signatures with an early-exit
+//! comparison, and the timing attack that recovers a valid signature from it.
+//!
+
+use byte_stream::ByteStream;
+use hmac::hmac;
+use sha1::Sha1;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Compares two byte streams one byte at a time, sleeping for `delay` after
+/// each matching byte and returning as soon as a mismatch is found.
+pub fn insecure_compare(a: &ByteStream, b: &ByteStream, delay: Duration) -> bool {
+    for (x, y) in a.iter().zip(b.iter()) {
+        if x != y {
+            return false;
+        }
+        thread::sleep(delay);
+    }
+    a.len() == b.len()
+}
+
+pub struct Server {
+    listener: TcpListener,
+    key: ByteStream,
+    delay: Duration,
+}
+
+impl Server {
+    /// Binds to `addr`; use port 0 to let the operating system pick one.
+    pub fn bind(addr: &str, key: ByteStream, delay: Duration) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            key,
+            delay,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers requests of the form `GET /test?file=...&signature=...` with
+    /// 200 if the signature is valid and 500 otherwise, until the listener
+    /// fails.
+    pub fn serve(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let mut stream = stream?;
+            let status = match parse_request(&mut stream) {
+                Some((file, signature)) => {
+                    let expected = hmac::<Sha1>(&self.key, &file);
+                    if insecure_compare(&expected, &signature, self.delay) {
+                        "200 OK"
+                    } else {
+                        "500 Internal Server Error"
+                    }
+                }
+                None => "400 Bad Request",
+            };
+            // A client that hangs up early shouldn't take the server down.
+            let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+        }
+        Ok(())
+    }
+
+    /// Serves requests on a background thread.
+    pub fn spawn(self) -> thread::JoinHandle<io::Result<()>> {
+        thread::spawn(move || self.serve())
+    }
+}
+
+/// Percent-encodes every byte of `value` other than the unreserved ones of
+/// RFC 3986, so that it can be carried in a query string.
+fn percent_encode(value: &[u8]) -> String {
+    value
+        .iter()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (*b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Undoes `percent_encode`, or any other percent-encoding. Returns `None` if a
+/// `%` isn't followed by two hex digits.
+fn percent_decode(value: &str) -> Option<ByteStream> {
+    let bytes = value.as_bytes();
+    let mut decoded = ByteStream::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(decoded)
+}
+
+fn parse_request(stream: &mut TcpStream) -> Option<(ByteStream, ByteStream)> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => (),
+            Err(_) => return None,
+        }
+    }
+
+    let target = request_line.split_whitespace().nth(1)?;
+    let query = target.split('?').nth(1)?;
+    let mut file = None;
+    let mut signature = None;
+    for param in query.split('&') {
+        let mut parts = param.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("file"), Some(value)) => file = percent_decode(value),
+            (Some("signature"), Some(value)) => signature = ByteStream::from_hex(value).ok(),
+            _ => (),
+        }
+    }
+    match (file, signature) {
+        (Some(file), Some(signature)) => Some((file, signature)),
+        _ => None,
+    }
+}
+
+/// Submits a signature to the server, returning whether it was accepted and
+/// how long the server took to answer.
+pub fn request(
+    addr: &SocketAddr,
+    file: &str,
+    signature: &ByteStream,
+) -> io::Result<(bool, Duration)> {
+    let start = Instant::now();
+    let mut stream = TcpStream::connect(addr)?;
+    write!(
+        stream,
+        "GET /test?file={}&signature={} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        percent_encode(file.as_bytes()),
+        signature.clone().into_hex()
+    )?;
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let elapsed = start.elapsed();
+    Ok((
+        status_line.split_whitespace().nth(1) == Some("200"),
+        elapsed,
+    ))
+}
+
+/// Finds the byte following `known` by timing every candidate `rounds` times
+/// and picking the one with the slowest median response. Fails with
+/// `InvalidInput` if `rounds` is zero, as there would be nothing to compare.
+pub fn recover_byte(
+    addr: &SocketAddr,
+    file: &str,
+    known: &ByteStream,
+    mac_len: usize,
+    rounds: usize,
+) -> io::Result<u8> {
+    if rounds == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "at least one round is needed",
+        ));
+    }
+    let mut timings: Vec<Vec<Duration>> = (0..256).map(|_| Vec::with_capacity(rounds)).collect();
+    for _ in 0..rounds {
+        for (candidate, samples) in timings.iter_mut().enumerate() {
+            let mut guess = known.clone();
+            guess.push(candidate as u8);
+            while guess.len() < mac_len {
+                guess.push(0);
+            }
+            let (accepted, elapsed) = request(addr, file, &guess)?;
+            if accepted {
+                return Ok(candidate as u8);
+            }
+            samples.push(elapsed);
+        }
+    }
+
+    let mut best = (0, Duration::from_secs(0));
+    for (candidate, samples) in timings.iter_mut().enumerate() {
+        samples.sort();
+        let median = samples[samples.len() / 2];
+        if median > best.1 {
+            best = (candidate as u8, median);
+        }
+    }
+    Ok(best.0)
+}
+
+/// Recovers a valid `mac_len`-byte signature for `file` one byte at a time.
+pub fn recover_signature(
+    addr: &SocketAddr,
+    file: &str,
+    mac_len: usize,
+    rounds: usize,
+) -> io::Result<ByteStream> {
+    let mut known = ByteStream::new();
+    while known.len() < mac_len {
+        let byte = recover_byte(addr, file, &known, mac_len, rounds)?;
+        known.push(byte);
+    }
+    Ok(known)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_compares_insecurely() {
+        let a = ByteStream::from_ascii("abc").unwrap();
+        let b = ByteStream::from_ascii("abd").unwrap();
+        assert!(insecure_compare(&a, &a, Duration::from_millis(0)));
+        assert!(!insecure_compare(&a, &b, Duration::from_millis(0)));
+        assert!(!insecure_compare(
+            &a,
+            &ByteStream::new(),
+            Duration::from_millis(0)
+        ));
+    }
+
+    #[test]
+    fn it_verifies_signatures() {
+        let key = ByteStream::from_ascii("YELLOW SUBMARINE").unwrap();
+        let server = Server::bind("127.0.0.1:0", key.clone(), Duration::from_millis(0)).unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn();
+
+        let signature = hmac::<Sha1>(&key, &ByteStream::from_ascii("foo").unwrap());
+        assert!(request(&addr, "foo", &signature).unwrap().0);
+        assert!(!request(&addr, "bar", &signature).unwrap().0);
+    }
+
+    #[test]
+    fn it_percent_encodes_file_names() {
+        let name = "a b&signature=00%/é";
+        assert_eq!(
+            percent_encode(name.as_bytes()),
+            "a%20b%26signature%3D00%25%2F%C3%A9"
+        );
+        assert_eq!(
+            percent_decode(&percent_encode(name.as_bytes())),
+            Some(ByteStream::from_bytes(name.as_bytes()))
+        );
+        assert_eq!(percent_decode("100%"), None);
+        assert_eq!(percent_decode("%zz"), None);
+    }
+
+    #[test]
+    fn it_verifies_signatures_of_awkward_file_names() {
+        let key = ByteStream::from_ascii("YELLOW SUBMARINE").unwrap();
+        let server = Server::bind("127.0.0.1:0", key.clone(), Duration::from_millis(0)).unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn();
+
+        let file = "my file&signature=00.txt";
+        let signature = hmac::<Sha1>(&key, &ByteStream::from_bytes(file.as_bytes()));
+        assert!(request(&addr, file, &signature).unwrap().0);
+        assert!(!request(&addr, "my file", &signature).unwrap().0);
+    }
+
+    #[test]
+    fn it_needs_at_least_one_round() {
+        let key = ByteStream::random(16);
+        let server = Server::bind("127.0.0.1:0", key, Duration::from_millis(0)).unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn();
+
+        let error = recover_byte(&addr, "foo", &ByteStream::new(), 20, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    #[ignore = "depends on real loopback timing, run with --ignored on an idle machine"]
+    fn it_recovers_leading_bytes_from_timing() {
+        let key = ByteStream::random(16);
+        let server = Server::bind("127.0.0.1:0", key.clone(), Duration::from_millis(3)).unwrap();
+        let addr = server.local_addr().unwrap();
+        server.spawn();
+
+        let expected = hmac::<Sha1>(&key, &ByteStream::from_ascii("foo").unwrap()).into_bytes();
+        let mut known = ByteStream::new();
+        for &b in expected.iter().take(2) {
+            let byte = recover_byte(&addr, "foo", &known, 20, 3).unwrap();
+            assert_eq!(byte, b);
+            known.push(byte);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//!
+//! A deliberately weak Merkle–Damgård hash over AES-128, with a state of only
+//! a few bytes so that generic attacks on iterated hashes finish quickly.
+//!
+
+use byte_stream::ByteStream;
+use std::cell::Cell;
+
+/// The size in bytes of the blocks the compression function consumes.
+pub const BLOCK_SIZE: usize = 16;
+
+const INITIAL_STATE: u32 = 0x0123_4567;
+
+pub struct ToyHash {
+    size: usize,
+    calls: Cell<u64>,
+}
+
+impl ToyHash {
+    /// A hash with a `size`-byte state, between two and four bytes.
+    pub fn new(size: usize) -> ToyHash {
+        assert!((2..=4).contains(&size));
+        ToyHash {
+            size,
+            calls: Cell::new(0),
+        }
+    }
+
+    /// The size of the state, and so of a digest, in bits.
+    pub fn bits(&self) -> usize {
+        self.size * 8
+    }
+
+    pub fn initial_state(&self) -> u32 {
+        INITIAL_STATE >> (32 - self.bits())
+    }
+
+    /// Encrypts `block` under the state, zero-padded to a key, and keeps the
+    /// leading bytes of the result as the next state.
+    pub fn compress(&self, state: u32, block: &[u8]) -> u32 {
+        assert!(block.len() == BLOCK_SIZE);
+        self.calls.set(self.calls.get() + 1);
+        let mut key = vec![0; BLOCK_SIZE];
+        key[..self.size].copy_from_slice(&state.to_be_bytes()[4 - self.size..]);
+        let mut output = ByteStream::from_bytes(block);
+        output.encrypt_aes_128_ecb(ByteStream::from(key));
+        output
+            .iter()
+            .take(self.size)
+            .fold(0, |acc, b| (acc << 8) | u32::from(*b))
+    }
+
+    /// Runs the compression function over `blocks`, which must be block
+    /// aligned, starting from `state`.
+    pub fn iterate(&self, state: u32, blocks: &ByteStream) -> u32 {
+        assert!(blocks.len().is_multiple_of(BLOCK_SIZE));
+        blocks
+            .iter_blocks(BLOCK_SIZE)
+            .fold(state, |state, block| self.compress(state, block))
+    }
+
+    /// The padding appended to a message of `message_len` bytes: `0x80`,
+    /// zeros, and the length in bits as a big-endian `u64`.
+    pub fn padding(message_len: usize) -> ByteStream {
+        let mut padding = ByteStream::from_bytes(&[0x80]);
+        while (message_len + padding.len()) % BLOCK_SIZE != BLOCK_SIZE - 8 {
+            padding.push(0);
+        }
+        padding.append(&mut ByteStream::from_bytes(
+            &(message_len as u64 * 8).to_be_bytes(),
+        ));
+        padding
+    }
+
+    pub fn digest(&self, message: &ByteStream) -> ByteStream {
+        let mut padded = message.clone();
+        padded.append(&mut Self::padding(message.len()));
+        let state = self.iterate(self.initial_state(), &padded);
+        ByteStream::from_bytes(&state.to_be_bytes()[4 - self.size..])
+    }
+
+    /// How many times the compression function has run.
+    pub fn calls(&self) -> u64 {
+        self.calls.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_pads_to_whole_blocks() {
+        for len in 0..40 {
+            let padding = ToyHash::padding(len);
+            assert_eq!((len + padding.len()) % BLOCK_SIZE, 0);
+            assert!(padding.len() > 8);
+        }
+    }
+
+    #[test]
+    fn it_hashes_into_the_state_size() {
+        let message = ByteStream::from_ascii("YELLOW SUBMARINE").unwrap();
+        for size in 2..5 {
+            let hash = ToyHash::new(size);
+            let digest = hash.digest(&message);
+            assert_eq!(digest.len(), size);
+            assert_eq!(digest, hash.digest(&message));
+            assert_eq!(hash.calls(), 4);
+        }
+        let hash = ToyHash::new(2);
+        assert!(hash.initial_state() < 1 << 16);
+        assert!(hash.digest(&message) != hash.digest(&ByteStream::new()));
+    }
+}